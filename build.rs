@@ -0,0 +1,204 @@
+//! Generates the configuration schema from `schema/ghostty-<VERSION>.txt`,
+//! the output of `ghostty +show-config --default --docs` of each release.
+//!
+//! Each key's type is inferred from its default value, whether it is
+//! repeatable from the default listing it more than once, and the releases
+//! that added or removed it from which files list it. Documentation and
+//! defaults come from the newest release that has the key. What the output
+//! does not tell, such as the values of enums and the platforms of a key,
+//! is refined by hand in `src/definitions/keys.rs`.
+//!
+//! Supporting a new Ghostty release is running `schema/capture.sh` with
+//! that release installed. The snapshots are never edited by hand.
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// A key as one release describes it.
+struct Key {
+    name: String,
+    documentation: String,
+    values: Vec<String>,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=schema");
+    let mut releases: Vec<([u32; 3], Vec<Key>)> = fs::read_dir("schema")
+        .expect("cannot read schema/")
+        .filter_map(|entry| {
+            let path = entry.expect("cannot read schema/").path();
+            let version = parse_version(&path)?;
+            let text = fs::read_to_string(&path).expect("cannot read a schema file");
+            Some((version, parse_show_config(&text)))
+        })
+        .collect();
+    releases.sort_by_key(|(version, _)| *version);
+    assert!(!releases.is_empty(), "no schema/ghostty-<VERSION>.txt");
+
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("schema.rs");
+    fs::write(out, generate(&releases)).expect("cannot write the schema");
+}
+
+/// The version in a `ghostty-1.2.0.txt` file name.
+fn parse_version(path: &Path) -> Option<[u32; 3]> {
+    let name = path.file_name()?.to_str()?;
+    let version = name.strip_prefix("ghostty-")?.strip_suffix(".txt")?;
+    let parts: Vec<u32> = version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    parts.try_into().ok()
+}
+
+/// Parses `# documentation` comments each followed by the `key = value`
+/// lines of the keys it documents. A key listed on several lines has
+/// several default values.
+fn parse_show_config(text: &str) -> Vec<Key> {
+    let mut keys: Vec<Key> = Vec::new();
+    let mut comment: Vec<&str> = Vec::new();
+    let mut documentation = String::new();
+    for line in text.lines() {
+        if let Some(doc) = line.strip_prefix('#') {
+            comment.push(doc.strip_prefix(' ').unwrap_or(doc));
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=') else {
+            // A blank line ends the keys the documentation applies to.
+            documentation.clear();
+            continue;
+        };
+        if !comment.is_empty() {
+            documentation = comment.join("\n").trim().to_string();
+            comment.clear();
+        }
+        let (name, value) = (name.trim(), value.trim());
+        match keys.iter_mut().find(|key| key.name == name) {
+            Some(key) => key.values.push(value.to_string()),
+            None => keys.push(Key {
+                name: name.to_string(),
+                documentation: documentation.clone(),
+                values: if value.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![value.to_string()]
+                },
+            }),
+        }
+    }
+    keys
+}
+
+/// The `ValueKind` a default value is written in.
+fn infer_kind(default: Option<&str>) -> &'static str {
+    let Some(default) = default else {
+        return "String";
+    };
+    let is_color = default.len() == 7
+        && default.starts_with('#')
+        && default[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if default == "true" || default == "false" {
+        "Bool"
+    } else if default.parse::<i64>().is_ok() {
+        "Integer"
+    } else if default.parse::<f64>().is_ok() {
+        "Float"
+    } else if is_color {
+        "Color"
+    } else {
+        "String"
+    }
+}
+
+fn version_expr([major, minor, patch]: [u32; 3]) -> String {
+    format!("Version::new({major}, {minor}, {patch})")
+}
+
+fn generate(releases: &[([u32; 3], Vec<Key>)]) -> String {
+    // Keys in the order the releases list them, with the keys a release
+    // added placed after the key preceding them in that release.
+    let mut order: Vec<&str> = Vec::new();
+    for (_, keys) in releases {
+        let mut after = None;
+        for key in keys {
+            match order.iter().position(|name| *name == key.name) {
+                Some(idx) => after = Some(idx),
+                None => {
+                    let idx = after.map_or(0, |idx| idx + 1);
+                    order.insert(idx, &key.name);
+                    after = Some(idx);
+                }
+            }
+        }
+    }
+    let by_release: Vec<HashMap<&str, &Key>> = releases
+        .iter()
+        .map(|(_, keys)| keys.iter().map(|key| (key.name.as_str(), key)).collect())
+        .collect();
+
+    let mut out = String::from("// Generated by build.rs from schema/. Do not edit.\n\n");
+    let versions: Vec<String> = releases
+        .iter()
+        .map(|(version, _)| version_expr(*version))
+        .collect();
+    writeln!(
+        out,
+        "/// The releases the schema has a snapshot of. The keys of any other\n/// release are those of the newest snapshot before it.\npub const SNAPSHOTS: &[Version] = &[{}];",
+        versions.join(", ")
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "/// The release the schema describes when no version is known.\npub const LATEST: Version = {};\n",
+        versions[versions.len() - 1]
+    )
+    .unwrap();
+
+    let mut documentation = String::new();
+    writeln!(out, "pub(super) const GENERATED_KEYS: &[ConfigKey] = &[").unwrap();
+    for name in order {
+        let present: Vec<usize> = (0..releases.len())
+            .filter(|&idx| by_release[idx].contains_key(name))
+            .collect();
+        let (first, last) = (present[0], present[present.len() - 1]);
+        let key = by_release[last][name];
+        let default = match key.values.as_slice() {
+            [value] => Some(value.as_str()),
+            _ => None,
+        };
+        write!(
+            out,
+            "    ConfigKey::new({name:?}, ValueKind::{})",
+            infer_kind(default)
+        )
+        .unwrap();
+        if let Some(default) = default {
+            write!(out, ".default({default:?})").unwrap();
+        }
+        if key.values.len() > 1 {
+            write!(out, ".repeatable()").unwrap();
+        }
+        if first > 0 {
+            write!(out, ".added({})", version_expr(releases[first].0)).unwrap();
+        }
+        if let Some((version, _)) = releases.get(last + 1) {
+            write!(out, ".removed({})", version_expr(*version)).unwrap();
+        }
+        writeln!(out, ",").unwrap();
+        if !key.documentation.is_empty() {
+            writeln!(
+                documentation,
+                "        {name:?} => Some({:?}),",
+                key.documentation
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "];\n").unwrap();
+    writeln!(
+        out,
+        "pub(super) fn documentation(name: &str) -> Option<&'static str> {{\n    match name {{\n{documentation}        _ => None,\n    }}\n}}"
+    )
+    .unwrap();
+    out
+}
@@ -0,0 +1,15 @@
+#!/bin/sh
+# Writes the output of `ghostty +show-config --default --docs` of the
+# installed Ghostty to schema/ghostty-<VERSION>.txt. The snapshots are only
+# ever replaced by this output: hand changes go in src/definitions/keys.rs.
+set -eu
+
+ghostty=${GHOSTTY:-ghostty}
+version=$("$ghostty" --version | sed -n 's/^Ghostty \([0-9][0-9.]*\).*/\1/p' | head -n 1)
+if [ -z "$version" ]; then
+    echo "error: cannot read the version of $ghostty" >&2
+    exit 1
+fi
+out="$(dirname "$0")/ghostty-$version.txt"
+"$ghostty" +show-config --default --docs >"$out"
+echo "wrote $out"
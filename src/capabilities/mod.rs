@@ -0,0 +1,198 @@
+//! What the server offers, depending on what the client supports.
+//!
+//! The client's capabilities decide the format of hover contents, whether
+//! completions may be snippets, whether diagnostics are pushed or pulled and
+//! which capabilities are registered dynamically after initialization.
+//!
+//! Only `workspace/didChangeConfiguration` is registered dynamically, as
+//! some clients send it only once it is registered. The document features
+//! are announced statically in the `initialize` result, which every client
+//! accepts, so they are not registered again.
+use lsp_types::notification::{DidChangeConfiguration, Notification as _};
+use lsp_types::{
+    ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionProviderCapability,
+    CompletionOptions, DiagnosticOptions, DiagnosticServerCapabilities, DocumentLinkOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, InlayHintServerCapabilities,
+    MarkupKind, OneOf, Registration, RegistrationParams, SelectionRangeProviderCapability,
+    ServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
+};
+
+use crate::diagnostics::SOURCE;
+use crate::migrate::SOURCE_MIGRATE;
+use crate::settings::SECTION;
+use crate::signature_help::TRIGGER_CHARACTERS;
+
+/// The parts of the client's capabilities the server adapts to. The
+/// defaults are what a client that declares nothing gets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientSupport {
+    /// Hover contents are sent as markdown rather than plain text, which is
+    /// the case whenever the client accepts markdown at all.
+    pub markdown_hover: bool,
+    /// Completion items may be snippets with placeholders.
+    pub snippets: bool,
+    /// The client pulls diagnostics with `textDocument/diagnostic`, so they
+    /// are not pushed.
+    pub pull_diagnostics: bool,
+    /// The client can be asked to pull diagnostics again with
+    /// `workspace/diagnostic/refresh`.
+    pub diagnostic_refresh: bool,
+    /// The client answers `workspace/configuration`.
+    pub configuration: bool,
+    /// The client accepts `client/registerCapability` for
+    /// `workspace/didChangeConfiguration`.
+    pub register_configuration: bool,
+}
+
+impl ClientSupport {
+    pub fn new(capabilities: &ClientCapabilities) -> Self {
+        let text_document = capabilities.text_document.as_ref();
+        let workspace = capabilities.workspace.as_ref();
+        Self {
+            markdown_hover: text_document
+                .and_then(|text_document| text_document.hover.as_ref())
+                .and_then(|hover| hover.content_format.as_ref())
+                .is_some_and(|formats| formats.contains(&MarkupKind::Markdown)),
+            snippets: text_document
+                .and_then(|text_document| text_document.completion.as_ref())
+                .and_then(|completion| completion.completion_item.as_ref())
+                .and_then(|item| item.snippet_support)
+                == Some(true),
+            pull_diagnostics: text_document
+                .is_some_and(|text_document| text_document.diagnostic.is_some()),
+            diagnostic_refresh: workspace
+                .and_then(|workspace| workspace.diagnostic.as_ref())
+                .and_then(|diagnostic| diagnostic.refresh_support)
+                == Some(true),
+            configuration: workspace.and_then(|workspace| workspace.configuration) == Some(true),
+            register_configuration: workspace
+                .and_then(|workspace| workspace.did_change_configuration.as_ref())
+                .and_then(|did_change| did_change.dynamic_registration)
+                == Some(true),
+        }
+    }
+}
+
+pub fn server_capabilities(client: &ClientSupport) -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            resolve_provider: Some(false),
+            trigger_characters: Some(vec!["/".to_string()]),
+            work_done_progress_options: Default::default(),
+            all_commit_characters: None,
+            completion_item: None,
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        inlay_hint_provider: Some(OneOf::Right(InlayHintServerCapabilities::Options(
+            Default::default(),
+        ))),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(TRIGGER_CHARACTERS.map(String::from).to_vec()),
+            retrigger_characters: None,
+            work_done_progress_options: Default::default(),
+        }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX, SOURCE_MIGRATE]),
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        })),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        }),
+        diagnostic_provider: client.pull_diagnostics.then(|| {
+            DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some(SOURCE.to_string()),
+                // Includes and themes affect the diagnostics of a file.
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: Default::default(),
+            })
+        }),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
+        ..Default::default()
+    }
+}
+
+/// The capabilities to register once the client is initialized, if any.
+/// Some clients only send `workspace/didChangeConfiguration` when it is
+/// registered.
+pub fn registrations(client: &ClientSupport) -> Option<RegistrationParams> {
+    let mut registrations = Vec::new();
+    if client.register_configuration {
+        registrations.push(Registration {
+            id: DidChangeConfiguration::METHOD.to_string(),
+            method: DidChangeConfiguration::METHOD.to_string(),
+            register_options: Some(serde_json::json!({ "section": SECTION })),
+        });
+    }
+    (!registrations.is_empty()).then_some(RegistrationParams { registrations })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_support() {
+        let capabilities: ClientCapabilities = serde_json::from_value(serde_json::json!({
+            "textDocument": {
+                "hover": { "contentFormat": ["markdown", "plaintext"] },
+                "completion": { "completionItem": { "snippetSupport": true } },
+                "diagnostic": {},
+            },
+            "workspace": {
+                "configuration": true,
+                "didChangeConfiguration": { "dynamicRegistration": true },
+                "diagnostic": { "refreshSupport": true },
+            },
+        }))
+        .unwrap();
+        let client = ClientSupport::new(&capabilities);
+        assert_eq!(
+            client,
+            ClientSupport {
+                markdown_hover: true,
+                snippets: true,
+                pull_diagnostics: true,
+                diagnostic_refresh: true,
+                configuration: true,
+                register_configuration: true,
+            }
+        );
+        assert!(server_capabilities(&client).diagnostic_provider.is_some());
+        assert_eq!(registrations(&client).unwrap().registrations.len(), 1);
+
+        let capabilities: ClientCapabilities = serde_json::from_value(serde_json::json!({
+            "textDocument": { "hover": { "contentFormat": ["plaintext", "markdown"] } },
+        }))
+        .unwrap();
+        let client = ClientSupport::new(&capabilities);
+        assert_eq!(
+            client,
+            ClientSupport {
+                markdown_hover: true,
+                ..Default::default()
+            }
+        );
+        assert!(server_capabilities(&client).diagnostic_provider.is_none());
+        assert!(registrations(&client).is_none());
+
+        let capabilities: ClientCapabilities = serde_json::from_value(serde_json::json!({
+            "textDocument": { "hover": { "contentFormat": ["plaintext"] } },
+        }))
+        .unwrap();
+        assert_eq!(ClientSupport::new(&capabilities), ClientSupport::default());
+    }
+}
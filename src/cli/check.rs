@@ -0,0 +1,411 @@
+//! `ghostty-lsp check`: the diagnostics of the language server on the
+//! command line.
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+use serde_json::{json, Value};
+
+use super::OutputFormat;
+use crate::definitions::{detect_version, Version};
+use crate::diagnostics::diagnostics;
+use crate::fonts::{FontCache, FontIndex};
+use crate::parser::byte_offset;
+use crate::paths;
+use crate::state::ServerState;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckOptions {
+    /// The files to check. Ghostty's default configuration file is checked
+    /// when there are none.
+    pub files: Vec<PathBuf>,
+    pub format: OutputFormat,
+    /// The Ghostty release to check against. Defaults to the one on `PATH`.
+    pub version: Option<Version>,
+}
+
+/// The diagnostics of one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub text: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl FileReport {
+    fn count(&self, severity: DiagnosticSeverity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Some(severity))
+            .count()
+    }
+}
+
+/// Checks the files against the fonts in `fonts` and writes the report to
+/// `out`. Returns the exit code: 1 when there are errors and 2 when a file
+/// could not be read.
+pub fn check(options: &CheckOptions, fonts: FontIndex, out: &mut impl Write) -> io::Result<i32> {
+    let files = if options.files.is_empty() {
+        paths::default_config_path().into_iter().collect()
+    } else {
+        options.files.clone()
+    };
+    let mut state = ServerState {
+        fonts: FontCache::ready(fonts),
+        ..Default::default()
+    };
+    state.settings.ghostty_version = options.version;
+    if options.version.is_none() {
+        state.detected_version = detect_version();
+    }
+
+    let mut reports = Vec::new();
+    let mut unreadable = false;
+    for path in files {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("error: cannot read `{}`: {err}", path.display());
+                unreadable = true;
+                continue;
+            }
+        };
+        let absolute = std::path::absolute(&path).unwrap_or_else(|_| path.clone());
+        let mut diagnostics = match Url::from_file_path(&absolute) {
+            Ok(uri) => diagnostics(&state, &uri, &text),
+            Err(()) => Vec::new(),
+        };
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.range.start, diagnostic.range.end));
+        reports.push(FileReport {
+            path,
+            text,
+            diagnostics,
+        });
+    }
+
+    match options.format {
+        OutputFormat::Human => render_human(&reports, out)?,
+        OutputFormat::Json => render_json(&reports, out)?,
+        OutputFormat::Sarif => render_sarif(&reports, out)?,
+        OutputFormat::Github => render_github(&reports, out)?,
+    }
+
+    let errors: usize = reports
+        .iter()
+        .map(|report| report.count(DiagnosticSeverity::ERROR))
+        .sum();
+    Ok(if unreadable {
+        2
+    } else if errors > 0 {
+        1
+    } else {
+        0
+    })
+}
+
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::HINT) => "help",
+        _ => "note",
+    }
+}
+
+fn code(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code.clone(),
+        Some(NumberOrString::Number(code)) => code.to_string(),
+        None => String::new(),
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {word}"),
+        _ => format!("{count} {word}s"),
+    }
+}
+
+/// Writes rustc-style diagnostics with a snippet of the offending line.
+fn render_human(reports: &[FileReport], out: &mut impl Write) -> io::Result<()> {
+    for report in reports {
+        let lines: Vec<&str> = report.text.lines().collect();
+        for diagnostic in &report.diagnostics {
+            let start = diagnostic.range.start;
+            writeln!(
+                out,
+                "{}[{}]: {}",
+                severity_name(diagnostic.severity),
+                code(diagnostic),
+                diagnostic.message
+            )?;
+            let line_number = (start.line + 1).to_string();
+            let gutter = " ".repeat(line_number.len());
+            writeln!(
+                out,
+                "{gutter}--> {}:{}:{}",
+                report.path.display(),
+                start.line + 1,
+                start.character + 1
+            )?;
+            let Some(line) = lines.get(start.line as usize) else {
+                writeln!(out)?;
+                continue;
+            };
+            // Ranges spanning several lines are underlined to the end of the
+            // first one.
+            let end = if diagnostic.range.end.line == start.line {
+                byte_offset(line, diagnostic.range.end.character)
+            } else {
+                line.len()
+            };
+            let start = byte_offset(line, start.character);
+            let indent = line[..start].chars().count();
+            let width = line[start..end.max(start)].chars().count().max(1);
+            writeln!(out, "{gutter} |")?;
+            writeln!(out, "{line_number} | {line}")?;
+            writeln!(
+                out,
+                "{gutter} | {}{}",
+                " ".repeat(indent),
+                "^".repeat(width)
+            )?;
+            writeln!(out)?;
+        }
+    }
+
+    let count = |severity| -> usize { reports.iter().map(|report| report.count(severity)).sum() };
+    let (errors, warnings) = (
+        count(DiagnosticSeverity::ERROR),
+        count(DiagnosticSeverity::WARNING),
+    );
+    let files = plural(reports.len(), "file");
+    if errors == 0 && warnings == 0 {
+        writeln!(out, "checked {files}: no problems found")
+    } else {
+        writeln!(
+            out,
+            "checked {files}: {}, {}",
+            plural(errors, "error"),
+            plural(warnings, "warning")
+        )
+    }
+}
+
+/// Writes one JSON object per diagnostic with 1-based positions.
+fn render_json(reports: &[FileReport], out: &mut impl Write) -> io::Result<()> {
+    let diagnostics: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.diagnostics.iter().map(|diagnostic| {
+                let range = diagnostic.range;
+                json!({
+                    "file": report.path.display().to_string(),
+                    "line": range.start.line + 1,
+                    "column": range.start.character + 1,
+                    "endLine": range.end.line + 1,
+                    "endColumn": range.end.character + 1,
+                    "severity": severity_name(diagnostic.severity),
+                    "code": code(diagnostic),
+                    "message": diagnostic.message,
+                })
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *out, &diagnostics)?;
+    writeln!(out)
+}
+
+/// Writes a SARIF 2.1.0 log, the format code scanning tools read.
+fn render_sarif(reports: &[FileReport], out: &mut impl Write) -> io::Result<()> {
+    let mut rules: Vec<String> = reports
+        .iter()
+        .flat_map(|report| report.diagnostics.iter().map(code))
+        .collect();
+    rules.sort();
+    rules.dedup();
+    let results: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.diagnostics.iter().map(|diagnostic| {
+                let level = match diagnostic.severity {
+                    Some(DiagnosticSeverity::ERROR) => "error",
+                    Some(DiagnosticSeverity::WARNING) => "warning",
+                    _ => "note",
+                };
+                let range = diagnostic.range;
+                json!({
+                    "ruleId": code(diagnostic),
+                    "level": level,
+                    "message": { "text": diagnostic.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": artifact_uri(&report.path) },
+                            "region": {
+                                "startLine": range.start.line + 1,
+                                "startColumn": range.start.character + 1,
+                                "endLine": range.end.line + 1,
+                                "endColumn": range.end.character + 1,
+                            }
+                        }
+                    }]
+                })
+            })
+        })
+        .collect();
+    let log = json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ghostty-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }]
+    });
+    serde_json::to_writer_pretty(&mut *out, &log)?;
+    writeln!(out)
+}
+
+/// SARIF wants URIs; relative paths are kept relative to the checkout.
+fn artifact_uri(path: &Path) -> String {
+    if path.is_absolute() {
+        if let Ok(uri) = Url::from_file_path(path) {
+            return uri.to_string();
+        }
+    }
+    path.display().to_string().replace('\\', "/")
+}
+
+/// Writes GitHub Actions workflow commands, which show up as annotations on
+/// pull requests.
+fn render_github(reports: &[FileReport], out: &mut impl Write) -> io::Result<()> {
+    for report in reports {
+        for diagnostic in &report.diagnostics {
+            let level = match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => "error",
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                _ => "notice",
+            };
+            let range = diagnostic.range;
+            writeln!(
+                out,
+                "::{level} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+                escape_property(&report.path.display().to_string()),
+                range.start.line + 1,
+                range.start.character + 1,
+                range.end.line + 1,
+                range.end.character + 1,
+                escape_property(&code(diagnostic)),
+                escape_data(&diagnostic.message)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::LATEST;
+    use crate::diagnostics::diagnostic;
+    use lsp_types::{Position, Range};
+
+    fn report() -> FileReport {
+        FileReport {
+            path: PathBuf::from("config"),
+            text: "font-size = 12\nlink = foo(bar\n".to_string(),
+            diagnostics: vec![diagnostic(
+                Range::new(Position::new(1, 10), Position::new(1, 14)),
+                DiagnosticSeverity::ERROR,
+                "invalid-link-regex",
+                "invalid regular expression: end pattern with unmatched parenthesis".to_string(),
+            )],
+        }
+    }
+
+    fn render(renderer: fn(&[FileReport], &mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = Vec::new();
+        renderer(&[report()], &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_render_human() {
+        assert_eq!(
+            render(render_human),
+            "\
+error[invalid-link-regex]: invalid regular expression: end pattern with unmatched parenthesis
+ --> config:2:11
+  |
+2 | link = foo(bar
+  |           ^^^^
+
+checked 1 file: 1 error, 0 warnings
+"
+        );
+    }
+
+    #[test]
+    fn test_render_github() {
+        assert_eq!(
+            render(render_github),
+            "::error file=config,line=2,col=11,endLine=2,endColumn=15,title=invalid-link-regex::invalid regular expression: end pattern with unmatched parenthesis\n"
+        );
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let log: Value = serde_json::from_str(&render(render_sarif)).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["id"],
+            "invalid-link-regex"
+        );
+        let result = &run["results"][0];
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "config");
+        assert_eq!(location["region"]["startColumn"], 11);
+    }
+
+    #[test]
+    fn test_check_exit_code() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config");
+        std::fs::write(&file, "link = (\n").unwrap();
+        let options = CheckOptions {
+            files: vec![file.clone()],
+            format: OutputFormat::Json,
+            version: Some(LATEST),
+        };
+        let check = |out: &mut Vec<u8>| check(&options, FontIndex::default(), out);
+        let mut out = Vec::new();
+        assert_eq!(check(&mut out).unwrap(), 1);
+        let diagnostics: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(diagnostics[0]["code"], "invalid-link-regex");
+
+        std::fs::write(&file, "font-size = 12\n").unwrap();
+        assert_eq!(check(&mut Vec::new()).unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(check(&mut Vec::new()).unwrap(), 2);
+    }
+}
@@ -0,0 +1,259 @@
+//! `ghostty-lsp explain` and `ghostty-lsp docs`: the schema the language
+//! server uses, rendered for people.
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::definitions::{
+    get_config_key, key_documentation, ConfigKey, ValueKind, ValueParameter, CONFIG_KEYS,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocsFormat {
+    #[default]
+    Markdown,
+    Json,
+    Html,
+}
+
+impl FromStr for DocsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            "html" => Ok(Self::Html),
+            _ => Err(format!(
+                "unknown format `{s}`, expected markdown, json or html"
+            )),
+        }
+    }
+}
+
+fn platforms(key: &ConfigKey) -> String {
+    if key.platforms.is_empty() {
+        return "all".to_string();
+    }
+    let names: Vec<&str> = key
+        .platforms
+        .iter()
+        .map(|platform| platform.name())
+        .collect();
+    names.join(", ")
+}
+
+fn default_value(key: &ConfigKey) -> &str {
+    key.default.unwrap_or("(unset)")
+}
+
+/// The type, default, platforms and repeatability of a key as label and
+/// value pairs.
+fn properties(key: &ConfigKey) -> Vec<(&'static str, String)> {
+    let mut properties = vec![
+        ("Type", key.kind.name()),
+        ("Default", default_value(key).to_string()),
+        ("Platforms", platforms(key)),
+        (
+            "Repeatable",
+            if key.repeatable { "yes" } else { "no" }.to_string(),
+        ),
+    ];
+    if let Some(note) = key.version_note() {
+        properties.push(("Versions", note));
+    }
+    properties
+}
+
+/// Prints everything known about `name`. Returns the exit code, 2 when the
+/// key does not exist.
+pub fn explain(name: &str, out: &mut impl Write) -> io::Result<i32> {
+    let Some(key) = get_config_key(name) else {
+        eprintln!("error: unknown configuration key `{name}`");
+        let similar: Vec<&str> = CONFIG_KEYS
+            .iter()
+            .map(|key| key.name)
+            .filter(|key| key.contains(name) || name.contains(key))
+            .collect();
+        if !similar.is_empty() {
+            eprintln!("similar keys: {}", similar.join(", "));
+        }
+        return Ok(2);
+    };
+    writeln!(out, "{}", key.name)?;
+    for (label, value) in properties(key) {
+        writeln!(out, "  {:<12}{value}", format!("{label}:"))?;
+    }
+    if let Some(parameters) = key.kind.parameters() {
+        writeln!(out, "  Parameters:")?;
+        for parameter in parameters {
+            writeln!(out, "    {}: {}", parameter.name, parameter_doc(parameter))?;
+        }
+    }
+    if let Some(documentation) = key_documentation(key.name) {
+        writeln!(out)?;
+        for line in documentation.lines() {
+            writeln!(out, "  {line}")?;
+        }
+    }
+    Ok(0)
+}
+
+/// Writes the whole registry in `format`.
+pub fn docs(format: DocsFormat, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        DocsFormat::Markdown => markdown(out),
+        DocsFormat::Json => {
+            let keys: Vec<Value> = CONFIG_KEYS.iter().map(key_json).collect();
+            serde_json::to_writer_pretty(&mut *out, &keys)?;
+            writeln!(out)
+        }
+        DocsFormat::Html => html(out),
+    }
+}
+
+fn parameter_doc(parameter: &ValueParameter) -> String {
+    parameter.doc().unwrap_or_default()
+}
+
+fn key_json(key: &ConfigKey) -> Value {
+    let values = match key.kind {
+        ValueKind::Enum(values) => Some(values),
+        _ => None,
+    };
+    let parameters: Option<Vec<Value>> = key.kind.parameters().map(|parameters| {
+        parameters
+            .iter()
+            .map(|parameter| json!({ "name": parameter.name, "doc": parameter.doc() }))
+            .collect()
+    });
+    json!({
+        "name": key.name,
+        "type": key.kind.name(),
+        "values": values,
+        "parameters": parameters,
+        "default": key.default,
+        "repeatable": key.repeatable,
+        "platforms": key.platforms.iter().map(|platform| platform.name()).collect::<Vec<_>>(),
+        "added": key.added.map(|version| version.to_string()),
+        "removed": key.removed.map(|version| version.to_string()),
+        "documentation": key_documentation(key.name),
+    })
+}
+
+fn markdown(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "# Ghostty configuration reference")?;
+    for key in CONFIG_KEYS.iter() {
+        writeln!(out, "\n## `{}`\n", key.name)?;
+        for (label, value) in properties(key) {
+            writeln!(out, "- **{label}:** {value}")?;
+        }
+        if let Some(parameters) = key.kind.parameters() {
+            writeln!(out, "- **Parameters:**")?;
+            for parameter in parameters {
+                writeln!(
+                    out,
+                    "  - `{}`: {}",
+                    parameter.name,
+                    parameter_doc(parameter)
+                )?;
+            }
+        }
+        if let Some(documentation) = key_documentation(key.name) {
+            // The prose is plain text, so keep its line breaks and
+            // indentation as they are.
+            writeln!(out, "\n```text\n{documentation}\n```")?;
+        }
+    }
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Ghostty configuration reference</title>\n</head>\n<body>\n<h1>Ghostty configuration reference</h1>"
+    )?;
+    writeln!(out, "<nav>\n<ul>")?;
+    for key in CONFIG_KEYS.iter() {
+        writeln!(out, "<li><a href=\"#{0}\">{0}</a></li>", key.name)?;
+    }
+    writeln!(out, "</ul>\n</nav>")?;
+    for key in CONFIG_KEYS.iter() {
+        writeln!(
+            out,
+            "<section id=\"{0}\">\n<h2><code>{0}</code></h2>\n<dl>",
+            key.name
+        )?;
+        for (label, value) in properties(key) {
+            writeln!(out, "<dt>{label}</dt><dd>{}</dd>", escape_html(&value))?;
+        }
+        if let Some(parameters) = key.kind.parameters() {
+            writeln!(out, "<dt>Parameters</dt><dd><ul>")?;
+            for parameter in parameters {
+                writeln!(
+                    out,
+                    "<li><code>{}</code>: {}</li>",
+                    escape_html(parameter.name),
+                    escape_html(&parameter_doc(parameter))
+                )?;
+            }
+            writeln!(out, "</ul></dd>")?;
+        }
+        writeln!(out, "</dl>")?;
+        if let Some(documentation) = key_documentation(key.name) {
+            writeln!(out, "<pre>{}</pre>", escape_html(&documentation))?;
+        }
+        writeln!(out, "</section>")?;
+    }
+    writeln!(out, "</body>\n</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain() {
+        let mut out = Vec::new();
+        assert_eq!(explain("background-blur-radius", &mut out).unwrap(), 0);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(
+            "background-blur-radius\n  Type:       integer\n  Default:    0\n  Platforms:  macOS\n  Repeatable: no\n"
+        ));
+        assert_eq!(explain("font", &mut Vec::new()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_docs_json_matches_schema() {
+        let mut out = Vec::new();
+        docs(DocsFormat::Json, &mut out).unwrap();
+        let keys: Vec<Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(keys.len(), CONFIG_KEYS.len());
+        let cursor_style = keys
+            .iter()
+            .find(|key| key["name"] == "cursor-style")
+            .unwrap();
+        assert_eq!(cursor_style["default"], "block");
+        assert_eq!(cursor_style["values"][1], "bar");
+        assert_eq!(cursor_style["platforms"], json!([]));
+    }
+
+    #[test]
+    fn test_docs_html_is_escaped() {
+        let mut out = Vec::new();
+        docs(DocsFormat::Html, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("<section id=\"keybind\">"));
+        assert!(out.contains("<dt>Type</dt><dd>one of block, bar, underline</dd>"));
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+        assert!(out.ends_with("</html>\n"));
+    }
+}
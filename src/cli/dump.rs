@@ -0,0 +1,106 @@
+//! `ghostty-lsp dump`: the effective configuration, as Ghostty would load
+//! it from a root file.
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::documents::Documents;
+use crate::parser::ConfigFile;
+use crate::paths;
+use crate::resolver::{resolve_config, theme_dirs, EffectiveValue};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DumpOptions {
+    /// The root configuration file. Ghostty's default configuration file is
+    /// used when there is none.
+    pub file: Option<PathBuf>,
+    /// Precedes each value with a comment naming the file and line that set
+    /// it.
+    pub with_provenance: bool,
+}
+
+/// Prints the effective configuration. Returns the exit code, 2 when the
+/// root file cannot be read.
+pub fn dump(options: &DumpOptions, out: &mut impl Write) -> io::Result<i32> {
+    let Some(path) = options.file.clone().or_else(paths::default_config_path) else {
+        eprintln!("error: no configuration file given and no default location");
+        return Ok(2);
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("error: cannot read `{}`: {err}", path.display());
+            return Ok(2);
+        }
+    };
+    // Includes are resolved against an absolute path like Ghostty does.
+    let path = std::path::absolute(&path).unwrap_or(path);
+    let values = resolve_config(
+        &Documents::default(),
+        &path,
+        &ConfigFile::parse(&text),
+        &theme_dirs(),
+    );
+    write_values(&values, options.with_provenance, out)?;
+    Ok(0)
+}
+
+fn write_values(
+    values: &[EffectiveValue],
+    with_provenance: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for value in values {
+        if with_provenance {
+            match value
+                .location
+                .as_ref()
+                .and_then(|location| Some((location.uri.to_file_path().ok()?, location)))
+            {
+                Some((path, location)) => writeln!(
+                    out,
+                    "# {}:{}",
+                    path.display(),
+                    location.range.start.line + 1
+                )?,
+                None => writeln!(out, "# default")?,
+            }
+        }
+        if value.value.is_empty() {
+            writeln!(out, "{} =", value.key)?;
+        } else {
+            writeln!(out, "{} = {}", value.key, value.value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_with_provenance() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-dump-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config");
+        std::fs::write(&file, "font-size = 14\nconfig-file = ?missing\n").unwrap();
+
+        let mut options = DumpOptions {
+            file: Some(file.clone()),
+            with_provenance: true,
+        };
+        let mut out = Vec::new();
+        assert_eq!(dump(&options, &mut out).unwrap(), 0);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(&format!("# {}:1\nfont-size = 14\n", file.display())));
+        assert!(out.contains("# default\ncursor-style = block\n"));
+
+        options.with_provenance = false;
+        let mut out = Vec::new();
+        dump(&options, &mut out).unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("# default"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(dump(&options, &mut Vec::new()).unwrap(), 2);
+    }
+}
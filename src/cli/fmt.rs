@@ -0,0 +1,271 @@
+//! `ghostty-lsp fmt`: the document formatter on the command line. It uses
+//! the same formatter and options file as `textDocument/formatting`, with
+//! `--config` standing in for the `format` setting.
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::formatting::{find_options, format, read_options};
+use crate::paths;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FmtOptions {
+    /// The files to format. Ghostty's default configuration file is
+    /// formatted when there are none.
+    pub files: Vec<PathBuf>,
+    /// Fails when a file is not formatted instead of rewriting it.
+    pub check: bool,
+    /// Prints a unified diff instead of rewriting the file.
+    pub diff: bool,
+    /// An options file whose `[format]` table applies to files without a
+    /// `.ghostty-lsp.toml`, like the `format` setting of the editor.
+    pub config: Option<PathBuf>,
+}
+
+/// Formats the files. Returns the exit code: 1 when `--check` finds an
+/// unformatted file and 2 when a file or its options cannot be read.
+pub fn fmt(options: &FmtOptions, out: &mut impl Write) -> io::Result<i32> {
+    let files = if options.files.is_empty() {
+        paths::default_config_path().into_iter().collect()
+    } else {
+        options.files.clone()
+    };
+    let fallback = match options.config.as_deref().map(read_options).transpose() {
+        Ok(fallback) => fallback,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return Ok(2);
+        }
+    };
+    let mut code = 0;
+    for path in files {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("error: cannot read `{}`: {err}", path.display());
+                code = 2;
+                continue;
+            }
+        };
+        let format_options = match find_options(&path) {
+            Ok(format_options) => format_options
+                .or_else(|| fallback.clone())
+                .unwrap_or_default(),
+            Err(err) => {
+                eprintln!("error: {err}");
+                code = 2;
+                continue;
+            }
+        };
+        let formatted = format(&text, &format_options);
+        if formatted == text {
+            continue;
+        }
+        let name = path.display().to_string();
+        if options.diff {
+            write!(out, "{}", unified_diff(&text, &formatted, &name))?;
+        }
+        if options.check {
+            if !options.diff {
+                writeln!(out, "would reformat {name}")?;
+            }
+            code = code.max(1);
+        } else if !options.diff {
+            std::fs::write(&path, formatted)?;
+        }
+    }
+    Ok(code)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Lines of context around each change.
+const CONTEXT: usize = 3;
+
+/// A unified diff between `old` and `new`, as printed by `diff -u`.
+pub fn unified_diff(old: &str, new: &str, name: &str) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+
+    // The longest common subsequence of lines, computed from the end so the
+    // edit script can be read off from the start.
+    let mut lengths = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    // Each operation with the line indices it applies to.
+    let mut ops: Vec<(Op, usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push((Op::Equal, i, j));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            ops.push((Op::Delete, i, j));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, i, j));
+            j += 1;
+        }
+    }
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _, _))| *op != Op::Equal)
+        .map(|(idx, _)| idx)
+        .collect();
+    let Some(&first) = changes.first() else {
+        return String::new();
+    };
+
+    // Group changes whose context overlaps into hunks.
+    let mut hunks = Vec::new();
+    let mut start = first.saturating_sub(CONTEXT);
+    let mut end = first;
+    for &change in &changes[1..] {
+        if change - end > 2 * CONTEXT {
+            hunks.push((start, (end + CONTEXT + 1).min(ops.len())));
+            start = change - CONTEXT;
+        }
+        end = change;
+    }
+    hunks.push((start, (end + CONTEXT + 1).min(ops.len())));
+
+    let mut diff = format!("--- {name}\n+++ {name}\n");
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|(op, _, _)| *op != Op::Insert).count();
+        let new_len = hunk.iter().filter(|(op, _, _)| *op != Op::Delete).count();
+        let (_, old_start, new_start) = hunk[0];
+        let header_start = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        diff.push_str(&format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            header_start(old_start, old_len),
+            header_start(new_start, new_len)
+        ));
+        for &(op, i, j) in hunk {
+            let (prefix, line) = match op {
+                Op::Equal => (' ', a[i]),
+                Op::Delete => ('-', a[i]),
+                Op::Insert => ('+', b[j]),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl";
+        assert_eq!(
+            unified_diff(old, new, "config"),
+            "\
+--- config
++++ config
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -9,3 +9,4 @@
+ i
+ j
+ k
++l
+\\ No newline at end of file
+"
+        );
+        assert_eq!(unified_diff(old, old, "config"), "");
+    }
+
+    #[test]
+    fn test_fmt_check_and_write() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-fmt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config");
+        std::fs::write(&file, "font-size=12\n").unwrap();
+        std::fs::write(dir.join(".ghostty-lsp.toml"), "[format]\nalign = true\n").unwrap();
+
+        let mut options = FmtOptions {
+            files: vec![file.clone()],
+            check: true,
+            diff: false,
+            config: None,
+        };
+        let mut out = Vec::new();
+        assert_eq!(fmt(&options, &mut out).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("would reformat {}\n", file.display())
+        );
+
+        options.check = false;
+        assert_eq!(fmt(&options, &mut Vec::new()).unwrap(), 0);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "font-size = 12\n");
+
+        std::fs::write(dir.join(".ghostty-lsp.toml"), "[format]\nalign = 1\n").unwrap();
+        assert_eq!(fmt(&options, &mut Vec::new()).unwrap(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fmt_config_applies_without_options_file() {
+        let dir =
+            std::env::temp_dir().join(format!("ghostty-lsp-fmt-config-{}", std::process::id()));
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        let config = dir.join("style.toml");
+        std::fs::write(&config, "[format]\nalign = true\n").unwrap();
+        let text = "a = 1\nlong-key = 2\n";
+        std::fs::write(dir.join("config"), text).unwrap();
+        std::fs::write(nested.join("config"), text).unwrap();
+        std::fs::write(nested.join(".ghostty-lsp.toml"), "[format]\n").unwrap();
+
+        let options = FmtOptions {
+            files: vec![dir.join("config"), nested.join("config")],
+            config: Some(config),
+            ..Default::default()
+        };
+        assert_eq!(fmt(&options, &mut Vec::new()).unwrap(), 0);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("config")).unwrap(),
+            "a        = 1\nlong-key = 2\n"
+        );
+        // The options file next to the configuration wins.
+        assert_eq!(
+            std::fs::read_to_string(nested.join("config")).unwrap(),
+            text
+        );
+
+        let missing = FmtOptions {
+            config: Some(dir.join("missing.toml")),
+            ..options
+        };
+        assert_eq!(fmt(&missing, &mut Vec::new()).unwrap(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
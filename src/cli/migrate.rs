@@ -0,0 +1,127 @@
+//! `ghostty-lsp migrate`: rewrites deprecated keys in configuration files
+//! and the files they include, like the "migrate" code action.
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use super::fmt::unified_diff;
+use crate::definitions::{detect_version, Version, LATEST};
+use crate::documents::Documents;
+use crate::includes::collect_includes;
+use crate::migrate::migrate;
+use crate::parser::ConfigFile;
+use crate::paths;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrateOptions {
+    /// The files to migrate along with everything they include. Ghostty's
+    /// default configuration file is migrated when there are none.
+    pub files: Vec<PathBuf>,
+    /// Fails when a file has deprecated keys instead of rewriting it.
+    pub check: bool,
+    /// Prints a unified diff instead of rewriting the file.
+    pub diff: bool,
+    /// The Ghostty release to migrate for. Defaults to the one on `PATH`.
+    pub version: Option<Version>,
+}
+
+/// Migrates the files. Returns the exit code: 1 when `--check` finds a
+/// deprecated key and 2 when a file cannot be read.
+pub fn migrate_files(options: &MigrateOptions, out: &mut impl Write) -> io::Result<i32> {
+    let roots = if options.files.is_empty() {
+        paths::default_config_path().into_iter().collect()
+    } else {
+        options.files.clone()
+    };
+    let version = options.version.or_else(detect_version).unwrap_or(LATEST);
+    let docs = Documents::default();
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    let mut code = 0;
+    for path in roots {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("error: cannot read `{}`: {err}", path.display());
+                code = 2;
+                continue;
+            }
+        };
+        let included = collect_includes(&docs, &path, &ConfigFile::parse(&text));
+        for path in std::iter::once(path).chain(included.into_iter().map(|file| file.path)) {
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if seen.insert(canonical) {
+                files.push(path);
+            }
+        }
+    }
+
+    for path in files {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("error: cannot read `{}`: {err}", path.display());
+                code = 2;
+                continue;
+            }
+        };
+        let migrated = migrate(&text, version);
+        if migrated == text {
+            continue;
+        }
+        let name = path.display().to_string();
+        if options.diff {
+            write!(out, "{}", unified_diff(&text, &migrated, &name))?;
+        }
+        if options.check {
+            if !options.diff {
+                writeln!(out, "would migrate {name}")?;
+            }
+            code = code.max(1);
+        } else if !options.diff {
+            std::fs::write(&path, migrated)?;
+            writeln!(out, "migrated {name}")?;
+        }
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_includes() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config");
+        let included = dir.join("blur");
+        std::fs::write(&config, "config-file = blur\nfont-size = 12\n").unwrap();
+        std::fs::write(&included, "background-blur-radius = 20\n").unwrap();
+
+        let mut options = MigrateOptions {
+            files: vec![config.clone()],
+            check: true,
+            diff: false,
+            version: Some(LATEST),
+        };
+        let mut out = Vec::new();
+        assert_eq!(migrate_files(&options, &mut out).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("would migrate {}\n", included.display())
+        );
+
+        options.version = Some(Version::new(1, 0, 1));
+        assert_eq!(migrate_files(&options, &mut Vec::new()).unwrap(), 0);
+
+        options.check = false;
+        options.version = Some(LATEST);
+        assert_eq!(migrate_files(&options, &mut Vec::new()).unwrap(), 0);
+        assert_eq!(
+            std::fs::read_to_string(&included).unwrap(),
+            "background-blur = 20\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,386 @@
+//! The command line interface. Without a subcommand the binary runs the
+//! language server, on stdio unless a transport is given.
+mod check;
+mod docs;
+mod dump;
+mod fmt;
+mod migrate;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub use check::{check, CheckOptions};
+pub use docs::{docs, explain, DocsFormat};
+pub use dump::{dump, DumpOptions};
+pub use fmt::{fmt, FmtOptions};
+pub use migrate::{migrate_files, MigrateOptions};
+
+use crate::logging::LogOptions;
+use crate::transport::Transport;
+
+pub const USAGE: &str = "\
+Usage: ghostty-lsp [OPTIONS]
+       ghostty-lsp <COMMAND>
+
+Commands:
+  check [FILES...]    Report problems in configuration files
+      --format <FORMAT>   Output format: human (default), json, sarif or github
+      --ghostty-version <VERSION>
+                          Check against this Ghostty release instead of the installed one
+  fmt [FILES...]      Format configuration files in place
+      --check             Fail instead of formatting when a file is not formatted
+      --diff              Print a unified diff instead of formatting
+      --config <PATH>     Read the style from this file when there is no .ghostty-lsp.toml
+  explain <KEY>       Print the documentation, type, default and platforms of a key
+  docs                Print the documentation of every key
+      --format <FORMAT>   Output format: markdown (default), json or html
+  dump [FILE]         Print the effective configuration after includes and themes
+      --with-provenance   Precede each value with the file and line that set it
+  migrate [FILES...]  Rename deprecated keys in configuration files and their includes
+      --check             Fail instead of migrating when a file has deprecated keys
+      --diff              Print a unified diff instead of migrating
+      --ghostty-version <VERSION>
+                          Migrate for this Ghostty release instead of the installed one
+
+Without a command the language server runs, on stdio by default.
+
+Options:
+  --stdio             Talk to the client over stdin and stdout
+  --listen <ADDR>     Wait for a client to connect to a TCP address
+  --connect <ADDR>    Connect to a client listening on a TCP address
+  --socket <PATH>     Serve every client that connects to a Unix domain socket
+  --log-level <FILTER>
+                      Log levels as `RUST_LOG` directives, e.g. `warn,ghostty_lsp=debug`
+  --log-file <PATH>   Append the log to a file instead of stderr
+  --log-contents      Include the text of documents in the log";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Serve(ServeOptions),
+    Help,
+    Check(CheckOptions),
+    Fmt(FmtOptions),
+    Explain(String),
+    Docs(DocsFormat),
+    Dump(DumpOptions),
+    Migrate(MigrateOptions),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServeOptions {
+    pub transport: Transport,
+    pub log: LogOptions,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+    Sarif,
+    Github,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            "github" => Ok(Self::Github),
+            _ => Err(format!(
+                "unknown format `{s}`, expected human, json, sarif or github"
+            )),
+        }
+    }
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Command::Serve(ServeOptions::default()));
+    };
+    match command.as_str() {
+        "-h" | "--help" | "help" => Ok(Command::Help),
+        "check" => {
+            let mut options = CheckOptions::default();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--format" => options.format = flag_value(arg, rest.next())?.parse()?,
+                    _ if arg.starts_with("--format=") => {
+                        options.format = arg["--format=".len()..].parse()?
+                    }
+                    "--ghostty-version" => {
+                        options.version = Some(flag_value(arg, rest.next())?.parse()?)
+                    }
+                    _ if arg.starts_with("--ghostty-version=") => {
+                        options.version = Some(arg["--ghostty-version=".len()..].parse()?)
+                    }
+                    _ if arg.starts_with('-') && arg != "-" => {
+                        return Err(format!("unknown option `{arg}`"))
+                    }
+                    _ => options.files.push(PathBuf::from(arg)),
+                }
+            }
+            Ok(Command::Check(options))
+        }
+        "fmt" => {
+            let mut options = FmtOptions::default();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--check" => options.check = true,
+                    "--diff" => options.diff = true,
+                    "--config" => {
+                        options.config = Some(PathBuf::from(flag_value(arg, rest.next())?))
+                    }
+                    _ if arg.starts_with("--config=") => {
+                        options.config = Some(PathBuf::from(&arg["--config=".len()..]))
+                    }
+                    _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                    _ => options.files.push(PathBuf::from(arg)),
+                }
+            }
+            Ok(Command::Fmt(options))
+        }
+        "explain" => match rest {
+            [key] => Ok(Command::Explain(key.clone())),
+            _ => Err("`explain` needs exactly one key".to_string()),
+        },
+        "docs" => {
+            let mut format = DocsFormat::default();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--format" => format = flag_value(arg, rest.next())?.parse()?,
+                    _ if arg.starts_with("--format=") => {
+                        format = arg["--format=".len()..].parse()?
+                    }
+                    _ => return Err(format!("unexpected argument `{arg}`")),
+                }
+            }
+            Ok(Command::Docs(format))
+        }
+        "dump" => {
+            let mut options = DumpOptions::default();
+            for arg in rest {
+                match arg.as_str() {
+                    "--with-provenance" => options.with_provenance = true,
+                    _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                    _ if options.file.is_none() => options.file = Some(PathBuf::from(arg)),
+                    _ => return Err(format!("unexpected argument `{arg}`")),
+                }
+            }
+            Ok(Command::Dump(options))
+        }
+        "migrate" => {
+            let mut options = MigrateOptions::default();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--check" => options.check = true,
+                    "--diff" => options.diff = true,
+                    "--ghostty-version" => {
+                        options.version = Some(flag_value(arg, rest.next())?.parse()?)
+                    }
+                    _ if arg.starts_with("--ghostty-version=") => {
+                        options.version = Some(arg["--ghostty-version=".len()..].parse()?)
+                    }
+                    _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                    _ => options.files.push(PathBuf::from(arg)),
+                }
+            }
+            Ok(Command::Migrate(options))
+        }
+        _ if command.starts_with("--") => parse_serve(args),
+        _ => Err(format!("unknown command `{command}`")),
+    }
+}
+
+/// Parses the options of the language server itself.
+fn parse_serve(args: &[String]) -> Result<Command, String> {
+    let mut options = ServeOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        let mut value = || match inline {
+            Some(value) => Ok(value),
+            None => flag_value(flag, args.next()),
+        };
+        match flag {
+            "--stdio" if inline.is_none() => options.transport = Transport::Stdio,
+            "--listen" => options.transport = Transport::Listen(value()?.to_string()),
+            "--connect" => options.transport = Transport::Connect(value()?.to_string()),
+            "--socket" => options.transport = Transport::Socket(PathBuf::from(value()?)),
+            "--log-level" => options.log.filter = Some(value()?.parse()?),
+            "--log-file" => options.log.file = Some(PathBuf::from(value()?)),
+            "--log-contents" if inline.is_none() => options.log.contents = true,
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    Ok(Command::Serve(options))
+}
+
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, String> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| format!("`{flag}` needs a value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::Version;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_check() {
+        assert_eq!(parse(&[]), Ok(Command::Serve(ServeOptions::default())));
+        assert_eq!(
+            parse(&["check", "a", "--format", "sarif", "b"]),
+            Ok(Command::Check(CheckOptions {
+                files: vec![PathBuf::from("a"), PathBuf::from("b")],
+                format: OutputFormat::Sarif,
+                version: None,
+            }))
+        );
+        assert_eq!(
+            parse(&["check", "--format=github", "--ghostty-version", "1.1.0"]),
+            Ok(Command::Check(CheckOptions {
+                files: Vec::new(),
+                format: OutputFormat::Github,
+                version: Some(Version::new(1, 1, 0)),
+            }))
+        );
+        assert!(parse(&["check", "--format"]).is_err());
+        assert!(parse(&["check", "--format", "xml"]).is_err());
+        assert!(parse(&["check", "--ghostty-version=one"]).is_err());
+        assert!(parse(&["lint"]).is_err());
+    }
+
+    fn transport(args: &[&str]) -> Result<Transport, String> {
+        match parse(args)? {
+            Command::Serve(options) => Ok(options.transport),
+            command => Err(format!("not serving: {command:?}")),
+        }
+    }
+
+    #[test]
+    fn test_parse_transport() {
+        assert_eq!(transport(&["--stdio"]), Ok(Transport::Stdio));
+        assert_eq!(
+            transport(&["--listen", "127.0.0.1:9257"]),
+            Ok(Transport::Listen("127.0.0.1:9257".to_string()))
+        );
+        assert_eq!(
+            transport(&["--connect=localhost:9257"]),
+            Ok(Transport::Connect("localhost:9257".to_string()))
+        );
+        assert_eq!(
+            transport(&["--socket", "/tmp/ghostty-lsp.sock"]),
+            Ok(Transport::Socket(PathBuf::from("/tmp/ghostty-lsp.sock")))
+        );
+        assert!(parse(&["--listen"]).is_err());
+        assert!(parse(&["--stdio=1"]).is_err());
+        assert!(parse(&["--port", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_logging() {
+        let Ok(Command::Serve(options)) = parse(&[
+            "--log-level=warn,ghostty_lsp=debug",
+            "--log-file",
+            "/tmp/ghostty-lsp.log",
+            "--log-contents",
+            "--socket",
+            "s",
+        ]) else {
+            panic!("not serving");
+        };
+        assert_eq!(
+            options.log,
+            LogOptions {
+                filter: Some("warn,ghostty_lsp=debug".parse().unwrap()),
+                file: Some(PathBuf::from("/tmp/ghostty-lsp.log")),
+                contents: true,
+            }
+        );
+        assert_eq!(options.transport, Transport::Socket(PathBuf::from("s")));
+        assert!(parse(&["--log-level", "chatty=x"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_fmt() {
+        assert_eq!(
+            parse(&["fmt", "--diff", "config", "--check"]),
+            Ok(Command::Fmt(FmtOptions {
+                files: vec![PathBuf::from("config")],
+                check: true,
+                diff: true,
+                config: None,
+            }))
+        );
+        assert_eq!(
+            parse(&["fmt", "--config", "style.toml"]),
+            Ok(Command::Fmt(FmtOptions {
+                config: Some(PathBuf::from("style.toml")),
+                ..Default::default()
+            }))
+        );
+        assert!(parse(&["fmt", "--config"]).is_err());
+        assert!(parse(&["fmt", "--write"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_explain_and_docs() {
+        assert_eq!(
+            parse(&["explain", "font-size"]),
+            Ok(Command::Explain("font-size".to_string()))
+        );
+        assert!(parse(&["explain"]).is_err());
+        assert_eq!(parse(&["docs"]), Ok(Command::Docs(DocsFormat::Markdown)));
+        assert_eq!(
+            parse(&["docs", "--format", "html"]),
+            Ok(Command::Docs(DocsFormat::Html))
+        );
+        assert!(parse(&["docs", "--format", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dump() {
+        assert_eq!(
+            parse(&["dump", "--with-provenance", "config"]),
+            Ok(Command::Dump(DumpOptions {
+                file: Some(PathBuf::from("config")),
+                with_provenance: true,
+            }))
+        );
+        assert_eq!(parse(&["dump"]), Ok(Command::Dump(DumpOptions::default())));
+        assert!(parse(&["dump", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_migrate() {
+        assert_eq!(
+            parse(&["migrate", "--check", "config", "--ghostty-version", "1.1"]),
+            Ok(Command::Migrate(MigrateOptions {
+                files: vec![PathBuf::from("config")],
+                check: true,
+                diff: false,
+                version: Some(Version::new(1, 1, 0)),
+            }))
+        );
+        assert!(parse(&["migrate", "--ghostty-version=next"]).is_err());
+        assert!(parse(&["migrate", "--write"]).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Parsing and validation of `font-codepoint-map` values.
+//!
+//! The syntax is `codepoints=fontname` where `codepoints` is a comma
+//! separated list of `U+ABCD` codepoints or `U+ABCD-U+DEFG` ranges.
+mod blocks;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::diagnostics::diagnostic;
+use crate::parser::{ConfigFile, Entry};
+
+pub use blocks::BLOCKS;
+
+pub const MAX_CODEPOINT: u32 = 0x10FFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodepointRange {
+    pub start: u32,
+    pub end: u32,
+    /// Byte offsets of the range within the value.
+    pub span: (usize, usize),
+}
+
+impl CodepointRange {
+    /// The number of codepoints in the range.
+    pub fn size(&self) -> u32 {
+        self.end - self.start + 1
+    }
+
+    pub fn overlaps(&self, other: &CodepointRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn label(&self) -> String {
+        if self.start == self.end {
+            format!("U+{:04X}", self.start)
+        } else {
+            format!("U+{:04X}-U+{:04X}", self.start, self.end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodepointError {
+    pub span: (usize, usize),
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodepointMap {
+    pub ranges: Vec<CodepointRange>,
+    pub font: Option<String>,
+    pub errors: Vec<CodepointError>,
+}
+
+pub fn parse_codepoint_map(value: &str) -> CodepointMap {
+    let mut map = CodepointMap::default();
+    let (codepoints, font) = match value.find('=') {
+        Some(idx) => (&value[..idx], Some(value[idx + 1..].trim())),
+        None => (value, None),
+    };
+    match font {
+        Some(font) if !font.is_empty() => map.font = Some(font.to_string()),
+        _ => map.errors.push(CodepointError {
+            span: (0, value.len()),
+            message: "expected `=fontname` after the codepoints".to_string(),
+        }),
+    }
+
+    let mut offset = 0;
+    for part in codepoints.split(',') {
+        let start = offset + part.len() - part.trim_start().len();
+        let span = (start, start + part.trim().len());
+        offset += part.len() + 1;
+        match parse_range(part.trim()) {
+            Ok((first, last)) => map.ranges.push(CodepointRange {
+                start: first,
+                end: last,
+                span,
+            }),
+            Err(message) => map.errors.push(CodepointError { span, message }),
+        }
+    }
+    map
+}
+
+fn parse_range(range: &str) -> Result<(u32, u32), String> {
+    let (first, last) = match range.split_once('-') {
+        Some((first, last)) => (parse_codepoint(first)?, parse_codepoint(last)?),
+        None => {
+            let codepoint = parse_codepoint(range)?;
+            (codepoint, codepoint)
+        }
+    };
+    if first > last {
+        return Err(format!(
+            "reversed range: U+{first:04X} is greater than U+{last:04X}"
+        ));
+    }
+    Ok((first, last))
+}
+
+fn parse_codepoint(codepoint: &str) -> Result<u32, String> {
+    let codepoint = codepoint.trim();
+    let hex = codepoint
+        .strip_prefix("U+")
+        .or_else(|| codepoint.strip_prefix("u+"))
+        .filter(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("malformed codepoint `{codepoint}`, expected U+ABCD"))?;
+    match u32::from_str_radix(hex, 16) {
+        Ok(value) if value <= MAX_CODEPOINT => Ok(value),
+        _ => Err(format!(
+            "`{codepoint}` is outside the Unicode range U+0000-U+10FFFF"
+        )),
+    }
+}
+
+/// The names of the Unicode blocks that `range` touches.
+pub fn block_names(range: &CodepointRange) -> Vec<&'static str> {
+    BLOCKS
+        .iter()
+        .filter(|(start, end, _)| *start <= range.end && range.start <= *end)
+        .map(|(_, _, name)| *name)
+        .collect()
+}
+
+/// Reports malformed mappings and ranges that a later mapping to another
+/// font overlaps. Ghostty gives precedence to later mappings, so the overlap
+/// is reported on the earlier one.
+pub fn codepoint_map_diagnostics(config: &ConfigFile) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut mappings: Vec<(&Entry, CodepointMap)> = Vec::new();
+    for entry in config.entries().filter(|e| e.key == "font-codepoint-map") {
+        if entry.value.is_empty() {
+            continue;
+        }
+        let map = parse_codepoint_map(&entry.value);
+        for error in &map.errors {
+            diagnostics.push(diagnostic(
+                entry.value_subrange(error.span.0, error.span.1),
+                DiagnosticSeverity::ERROR,
+                "invalid-codepoint-map",
+                error.message.clone(),
+            ));
+        }
+        mappings.push((entry, map));
+    }
+
+    for (idx, (entry, map)) in mappings.iter().enumerate() {
+        for range in &map.ranges {
+            let winner = mappings[idx + 1..]
+                .iter()
+                .rev()
+                .filter_map(|(later, later_map)| {
+                    later_map
+                        .ranges
+                        .iter()
+                        .find(|other| other.overlaps(range))
+                        .map(|other| (later, later_map, other))
+                })
+                .next();
+            let Some((later, later_map, other)) = winner else {
+                continue;
+            };
+            if later_map.font == map.font {
+                continue;
+            }
+            let font = later_map.font.as_deref().unwrap_or("");
+            diagnostics.push(diagnostic(
+                entry.value_subrange(range.span.0, range.span.1),
+                DiagnosticSeverity::WARNING,
+                "overlapping-codepoint-map",
+                format!(
+                    "{} overlaps {} on line {}; `{font}` wins for the overlapping codepoints",
+                    range.label(),
+                    other.label(),
+                    later.line + 1
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Hover text for the codepoint range under the byte offset `idx` of the
+/// entry's value.
+pub fn codepoint_range_hover(entry: &Entry, idx: usize) -> Option<String> {
+    let map = parse_codepoint_map(&entry.value);
+    let range = map
+        .ranges
+        .iter()
+        .find(|range| range.span.0 <= idx && idx <= range.span.1)?;
+    let blocks = block_names(range);
+    let blocks = if blocks.is_empty() {
+        "No block".to_string()
+    } else {
+        blocks.join(", ")
+    };
+    let plural = if range.size() == 1 { "" } else { "s" };
+    Some(format!(
+        "{}: {} codepoint{plural}\nBlocks: {blocks}",
+        range.label(),
+        range.size()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codepoint_map() {
+        let map = parse_codepoint_map("U+E000-U+E0FF, U+F000=Symbols Nerd Font");
+        assert_eq!(map.font.as_deref(), Some("Symbols Nerd Font"));
+        assert!(map.errors.is_empty());
+        assert_eq!(
+            map.ranges,
+            vec![
+                CodepointRange {
+                    start: 0xE000,
+                    end: 0xE0FF,
+                    span: (0, 13)
+                },
+                CodepointRange {
+                    start: 0xF000,
+                    end: 0xF000,
+                    span: (15, 21)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_codepoint_map_errors() {
+        let messages: Vec<_> = parse_codepoint_map("U+ZZ,U+0200-U+0100,U+110000")
+            .errors
+            .into_iter()
+            .map(|error| (error.span, error.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    (0, 27),
+                    "expected `=fontname` after the codepoints".to_string()
+                ),
+                (
+                    (0, 4),
+                    "malformed codepoint `U+ZZ`, expected U+ABCD".to_string()
+                ),
+                (
+                    (5, 18),
+                    "reversed range: U+0200 is greater than U+0100".to_string()
+                ),
+                (
+                    (19, 27),
+                    "`U+110000` is outside the Unicode range U+0000-U+10FFFF".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_mappings() {
+        let config = ConfigFile::parse(
+            "font-codepoint-map = U+E000-U+E0FF=A\nfont-codepoint-map = U+E0A0=B\n",
+        );
+        let diagnostics = codepoint_map_diagnostics(&config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 0);
+        assert_eq!(
+            diagnostics[0].message,
+            "U+E000-U+E0FF overlaps U+E0A0 on line 2; `B` wins for the overlapping codepoints"
+        );
+    }
+
+    #[test]
+    fn test_overlapping_mappings_to_the_same_font() {
+        let config = ConfigFile::parse(
+            "font-codepoint-map = U+E000-U+E0FF=A\nfont-codepoint-map = U+E0A0=A\n",
+        );
+        assert!(codepoint_map_diagnostics(&config).is_empty());
+    }
+
+    #[test]
+    fn test_codepoint_range_hover() {
+        let config = ConfigFile::parse("font-codepoint-map = U+0041,U+E000-U+F8FF=A");
+        let entry = config.entry_at_line(0).unwrap();
+        assert_eq!(
+            codepoint_range_hover(entry, 10).unwrap(),
+            "U+E000-U+F8FF: 6400 codepoints\nBlocks: Private Use Area"
+        );
+        assert_eq!(
+            codepoint_range_hover(entry, 0).unwrap(),
+            "U+0041: 1 codepoint\nBlocks: Basic Latin"
+        );
+    }
+}
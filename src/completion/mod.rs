@@ -0,0 +1,338 @@
+//! Completion of configuration keys and values.
+use std::path::Path;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemTag, CompletionTextEdit, InsertTextFormat,
+    Position, Range, TextEdit,
+};
+
+use crate::definitions::{get_deprecation, ConfigKey, ValueKind, Version, CONFIG_KEYS};
+use crate::fonts::{configured_family, feature_description, FontIndex, FontTables};
+use crate::parser::{ConfigFile, LineKind};
+use crate::working_directory::directory_completions;
+
+/// Completes the key or value under `position`. `base_dir` is the directory
+/// of the document, which relative paths are resolved against. Keys are
+/// those of Ghostty `version`. Items only contain snippets when `snippets` is
+/// set.
+pub fn completions(
+    base_dir: Option<&Path>,
+    config: &ConfigFile,
+    position: Position,
+    fonts: Option<&FontIndex>,
+    snippets: bool,
+    version: Version,
+) -> Vec<CompletionItem> {
+    let entry = match config
+        .lines
+        .get(position.line as usize)
+        .map(|line| &line.kind)
+    {
+        Some(LineKind::Entry(entry)) => entry,
+        Some(LineKind::Comment) => return Vec::new(),
+        // A new key can go on a blank line.
+        Some(LineKind::Blank) | None => return key_items(version, snippets, None),
+    };
+    if position.character <= entry.key_range.end.character {
+        return key_items(version, snippets, Some(entry.key_range));
+    }
+    if position.character < entry.value_range.start.character {
+        return Vec::new();
+    }
+    if entry.key == "working-directory" {
+        return match entry.value_offset(position.character) {
+            Some(offset) => directory_completions(base_dir, entry, offset),
+            None => Vec::new(),
+        };
+    }
+    let Some(fonts) = fonts else {
+        return Vec::new();
+    };
+
+    if entry.key.starts_with("font-family") {
+        return value_items(fonts.families(), "font family");
+    }
+    let is_style = entry.key.starts_with("font-style");
+    let is_feature = entry.key == "font-feature";
+    let is_variation = entry.key.starts_with("font-variation");
+    if !(is_style || is_feature || is_variation) {
+        return Vec::new();
+    }
+    // `font-style-bold` and `font-variation-bold` use `font-family-bold`,
+    // falling back to `font-family` when that is not set.
+    let Some(family) = configured_family(config, &entry.key) else {
+        return Vec::new();
+    };
+    if is_style {
+        return value_items(fonts.styles(family), family);
+    }
+    let Some(tables) = FontTables::for_family(fonts, family) else {
+        return Vec::new();
+    };
+    if is_feature {
+        feature_items(&tables)
+    } else {
+        axis_items(&tables, snippets)
+    }
+}
+
+/// Items for the keys Ghostty `version` knows, with the deprecated ones
+/// tagged. As a snippet the value is a choice of the values of enums and
+/// booleans. On an existing entry, whose key is at `key_range`, only the key
+/// is replaced.
+fn key_items(version: Version, snippets: bool, key_range: Option<Range>) -> Vec<CompletionItem> {
+    CONFIG_KEYS
+        .iter()
+        .filter(|key| key.available_in(version))
+        .map(|key| {
+            let detail = match key.version_note() {
+                Some(note) => format!("{} ({note})", key.kind.name()),
+                None => key.kind.name(),
+            };
+            let deprecated = get_deprecation(key.name).is_some_and(|deprecation| {
+                deprecation.renames() && deprecation.applies_to(version)
+            });
+            let mut item = CompletionItem {
+                label: key.name.to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(detail),
+                tags: deprecated.then(|| vec![CompletionItemTag::DEPRECATED]),
+                ..Default::default()
+            };
+            if let Some(range) = key_range {
+                let edit = TextEdit::new(range, key.name.to_string());
+                item.text_edit = Some(CompletionTextEdit::Edit(edit));
+                return item;
+            }
+            let snippet = snippets.then(|| key_snippet(key)).flatten();
+            item.insert_text_format = snippet.is_some().then_some(InsertTextFormat::SNIPPET);
+            item.insert_text = Some(snippet.unwrap_or_else(|| format!("{} = ", key.name)));
+            item
+        })
+        .collect()
+}
+
+fn key_snippet(key: &ConfigKey) -> Option<String> {
+    let values: &[&str] = match key.kind {
+        ValueKind::Enum(values) => values,
+        ValueKind::Bool => &["true", "false"],
+        _ => return None,
+    };
+    Some(format!("{} = ${{1|{}|}}", key.name, values.join(",")))
+}
+
+/// Items to enable and disable each feature of the font.
+fn feature_items(tables: &FontTables) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+    for tag in &tables.features {
+        let description = feature_description(tag);
+        for (label, action) in [(tag.clone(), "enable"), (format!("-{tag}"), "disable")] {
+            let detail = match &description {
+                Some(description) => format!("{action} {description}"),
+                None => action.to_string(),
+            };
+            items.push(CompletionItem {
+                label,
+                kind: Some(CompletionItemKind::VALUE),
+                detail: Some(detail),
+                ..Default::default()
+            });
+        }
+    }
+    items
+}
+
+/// Items for each axis of the font. As a snippet the value is a
+/// placeholder holding the axis default.
+fn axis_items(tables: &FontTables, snippets: bool) -> Vec<CompletionItem> {
+    tables
+        .axes
+        .iter()
+        .map(|axis| CompletionItem {
+            label: axis.tag.clone(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!(
+                "min {}, default {}, max {}",
+                axis.min, axis.default, axis.max
+            )),
+            insert_text: Some(if snippets {
+                format!("{}=${{1:{}}}", axis.tag, axis.default)
+            } else {
+                format!("{}=", axis.tag)
+            }),
+            insert_text_format: snippets.then_some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn value_items(values: Vec<&str>, detail: &str) -> Vec<CompletionItem> {
+    values
+        .into_iter()
+        .map(|value| CompletionItem {
+            label: value.to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(detail.to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::definitions::LATEST;
+    use crate::fonts::{FontFace, VariationAxis};
+
+    fn labels(text: &str, position: Position) -> Vec<String> {
+        let face = |family: &str, style: &str| FontFace {
+            families: vec![family.to_string()],
+            style: style.to_string(),
+            path: PathBuf::from("/fonts/font.ttf"),
+            index: 0,
+        };
+        let index = FontIndex::new(vec![
+            face("Iosevka", "Heavy"),
+            face("Iosevka", "Regular"),
+            face("Fira Code", "Bold"),
+        ]);
+        completions(
+            None,
+            &ConfigFile::parse(text),
+            position,
+            Some(&index),
+            false,
+            LATEST,
+        )
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+    }
+
+    #[test]
+    fn test_complete_font_family() {
+        assert_eq!(
+            labels("font-family = ", Position::new(0, 14)),
+            vec!["Fira Code", "Iosevka"]
+        );
+        assert!(labels("font-family = ", Position::new(0, 12)).is_empty());
+    }
+
+    #[test]
+    fn test_complete_keys_of_version() {
+        let keys = |version| -> Vec<CompletionItem> {
+            completions(
+                None,
+                &ConfigFile::parse(
+                    "# keys
+",
+                ),
+                Position::new(1, 0),
+                None,
+                true,
+                version,
+            )
+        };
+        let latest = keys(LATEST);
+        assert!(latest
+            .iter()
+            .any(|item| item.label == "window-show-tab-bar"));
+        assert!(!latest.iter().any(|item| item.label == "gtk-adwaita"));
+        let old = keys(Version::new(1, 0, 1));
+        assert!(!old.iter().any(|item| item.label == "window-show-tab-bar"));
+        let radius = |items: &[CompletionItem]| {
+            let item = items
+                .iter()
+                .find(|item| item.label == "background-blur-radius")
+                .unwrap();
+            item.tags.clone()
+        };
+        assert_eq!(radius(&latest), Some(vec![CompletionItemTag::DEPRECATED]));
+        assert_eq!(radius(&old), None);
+        let cursor_style = old
+            .iter()
+            .find(|item| item.label == "cursor-style")
+            .unwrap();
+        assert_eq!(
+            cursor_style.insert_text.as_deref(),
+            Some("cursor-style = ${1|block,bar,underline|}")
+        );
+        assert!(labels("# font", Position::new(0, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_complete_key_of_existing_entry() {
+        let items = completions(
+            None,
+            &ConfigFile::parse("font-fam = 12\n"),
+            Position::new(0, 4),
+            None,
+            true,
+            LATEST,
+        );
+        let item = items
+            .iter()
+            .find(|item| item.label == "font-family")
+            .unwrap();
+        assert_eq!(item.insert_text, None);
+        assert_eq!(
+            item.text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 8)),
+                "font-family".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_complete_font_style_for_family() {
+        let text = "font-family = Iosevka\nfont-family-bold = Fira Code\nfont-style = \nfont-style-bold = ";
+        assert_eq!(labels(text, Position::new(2, 13)), vec!["Heavy", "Regular"]);
+        assert_eq!(labels(text, Position::new(3, 18)), vec!["Bold"]);
+    }
+
+    #[test]
+    fn test_feature_and_axis_items() {
+        let tables = FontTables {
+            features: ["calt".to_string(), "ss01".to_string()].into(),
+            axes: vec![VariationAxis {
+                tag: "wght".to_string(),
+                min: 100.0,
+                default: 400.0,
+                max: 900.0,
+            }],
+        };
+        let features: Vec<_> = feature_items(&tables)
+            .into_iter()
+            .map(|item| (item.label, item.detail.unwrap()))
+            .collect();
+        assert_eq!(
+            features,
+            vec![
+                (
+                    "calt".to_string(),
+                    "enable Contextual Alternates".to_string()
+                ),
+                (
+                    "-calt".to_string(),
+                    "disable Contextual Alternates".to_string()
+                ),
+                ("ss01".to_string(), "enable Stylistic Set 1".to_string()),
+                ("-ss01".to_string(), "disable Stylistic Set 1".to_string()),
+            ]
+        );
+        let axes = axis_items(&tables, false);
+        assert_eq!(axes[0].label, "wght");
+        assert_eq!(
+            axes[0].detail.as_deref(),
+            Some("min 100, default 400, max 900")
+        );
+        assert_eq!(axes[0].insert_text.as_deref(), Some("wght="));
+        assert_eq!(axes[0].insert_text_format, None);
+        let axes = axis_items(&tables, true);
+        assert_eq!(axes[0].insert_text.as_deref(), Some("wght=${1:400}"));
+        assert_eq!(axes[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+    }
+}
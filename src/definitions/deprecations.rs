@@ -0,0 +1,91 @@
+use super::versions::Version;
+
+/// A key Ghostty still reads but has replaced with another one, or values
+/// of a key that Ghostty still reads but spells differently now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deprecation {
+    /// The old name of the key.
+    pub name: &'static str,
+    /// The key to use instead. It is `name` when only values changed.
+    pub replacement: &'static str,
+    /// The release that introduced the replacement.
+    pub since: Version,
+    /// Old values and what they are written as under the replacement.
+    /// Values that are not listed carry over unchanged.
+    pub values: &'static [(&'static str, &'static str)],
+}
+
+impl Deprecation {
+    const fn new(name: &'static str, replacement: &'static str, since: Version) -> Self {
+        Self {
+            name,
+            replacement,
+            since,
+            values: &[],
+        }
+    }
+
+    /// Values of `name` that are deprecated while the key itself is not.
+    const fn values_of(name: &'static str, since: Version) -> Self {
+        Self::new(name, name, since)
+    }
+
+    const fn values(mut self, values: &'static [(&'static str, &'static str)]) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Whether the key is renamed, rather than only some of its values.
+    pub fn renames(&self) -> bool {
+        self.name != self.replacement
+    }
+
+    /// Whether an entry with `value` is deprecated: every entry of a
+    /// renamed key, otherwise only the listed values.
+    pub fn deprecates(&self, value: &str) -> bool {
+        self.renames() || self.values.iter().any(|(old, _)| *old == value)
+    }
+
+    /// Whether the replacement exists in Ghostty `version`, which is when
+    /// migrating makes sense.
+    pub fn applies_to(&self, version: Version) -> bool {
+        self.since <= version
+    }
+
+    /// `value` as written under the replacement.
+    pub fn migrate_value<'a>(&self, value: &'a str) -> &'a str {
+        self.values
+            .iter()
+            .find(|(old, _)| *old == value)
+            .map_or(value, |(_, new)| new)
+    }
+}
+
+pub const DEPRECATIONS: &[Deprecation] = &[
+    // The radius became part of `background-blur`, which also takes
+    // booleans.
+    Deprecation::new(
+        "background-blur-radius",
+        "background-blur",
+        Version::new(1, 1, 0),
+    )
+    .values(&[("0", "false")]),
+    // `window-decoration` took the decorations to draw instead of a boolean.
+    Deprecation::values_of("window-decoration", Version::new(1, 1, 0))
+        .values(&[("true", "auto"), ("false", "none")]),
+    // Hiding the menu bar in non-native fullscreen is spelled `padded-notch`,
+    // which also keeps the content clear of the notch.
+    Deprecation::values_of("macos-non-native-fullscreen", Version::new(1, 1, 0))
+        .values(&[("true", "padded-notch")]),
+    // Whether to draw the GTK titlebar became the style of the titlebar:
+    // without one, the tabs take its place.
+    Deprecation::new("gtk-titlebar", "gtk-titlebar-style", Version::new(1, 2, 0))
+        .values(&[("true", "native"), ("false", "tabs")]),
+];
+
+/// Looks up the deprecation of a key by its old name.
+pub fn get_deprecation(name: &str) -> Option<&'static Deprecation> {
+    DEPRECATIONS
+        .iter()
+        .find(|deprecation| deprecation.name == name)
+}
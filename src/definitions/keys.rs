@@ -0,0 +1,577 @@
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use super::schema::{documentation, GENERATED_KEYS};
+use super::versions::Version;
+
+/// The type of value a configuration key accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    Bool,
+    Integer,
+    Float,
+    String,
+    Color,
+    Path,
+    /// One of a fixed set of values.
+    Enum(&'static [&'static str]),
+    /// An integer or a percentage such as `20%` or `-1`.
+    Adjustment,
+    /// `N=COLOR`
+    Palette,
+    /// `U+XXXX-U+YYYY=fontname`
+    CodepointMap,
+    /// `id=value`
+    FontVariation,
+    /// A feature tag, optionally prefixed with `-`.
+    FontFeature,
+    /// `trigger=action:param`
+    Keybind,
+    /// A regular expression and the action to take on its matches.
+    Link,
+    /// A comma separated list of values.
+    List,
+}
+
+/// One part of a structured value such as the `N` in `N=COLOR`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueParameter {
+    pub name: &'static str,
+    /// The key whose documentation describes the parameter.
+    key: &'static str,
+    /// Where the description starts in that documentation.
+    excerpt: &'static str,
+    /// How many sentences the description runs for.
+    sentences: usize,
+}
+
+impl ValueParameter {
+    const fn new(
+        name: &'static str,
+        key: &'static str,
+        excerpt: &'static str,
+        sentences: usize,
+    ) -> Self {
+        Self {
+            name,
+            key,
+            excerpt,
+            sentences,
+        }
+    }
+
+    /// The sentences of the schema documentation that describe the
+    /// parameter, or `None` when that documentation no longer has them.
+    pub fn doc(&self) -> Option<String> {
+        let documentation = documentation(self.key)?;
+        // Join the lines so sentences that wrap read as one.
+        let text = documentation
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let rest = &text[text.find(self.excerpt)?..];
+        let mut end = rest.len();
+        let mut sentences = 0;
+        for (idx, _) in rest.match_indices(". ") {
+            sentences += 1;
+            if sentences == self.sentences {
+                end = idx + 1;
+                break;
+            }
+        }
+        Some(rest[..end].to_string())
+    }
+}
+
+const PALETTE_PARAMETERS: &[ValueParameter] = &[
+    ValueParameter::new("N", "palette", "The 0 to 255 correspond", 1),
+    ValueParameter::new("COLOR", "palette", "HEXCODE is a typical RGB", 1),
+];
+
+const CODEPOINT_MAP_PARAMETERS: &[ValueParameter] = &[
+    ValueParameter::new(
+        "U+XXXX-U+YYYY",
+        "font-codepoint-map",
+        "Codepoints must be specified",
+        3,
+    ),
+    ValueParameter::new(
+        "fontname",
+        "font-codepoint-map",
+        "The font name is the same",
+        1,
+    ),
+];
+
+const FONT_VARIATION_PARAMETERS: &[ValueParameter] = &[
+    ValueParameter::new("id", "font-variation", "An axis identifier is", 2),
+    ValueParameter::new("value", "font-variation", "Invalid ids and values are", 3),
+];
+
+const KEYBIND_PARAMETERS: &[ValueParameter] = &[
+    ValueParameter::new("trigger", "keybind", "Trigger: ", 2),
+    ValueParameter::new("action", "keybind", "Action is the action", 2),
+    ValueParameter::new("param", "keybind", "The parameter is taken", 2),
+];
+
+impl ValueKind {
+    /// The parts of a structured value, in the order they are written, or
+    /// `None` for values without a mini-grammar. Their descriptions come
+    /// from the schema documentation of the key that has the grammar.
+    pub fn parameters(&self) -> Option<&'static [ValueParameter]> {
+        match self {
+            Self::Palette => Some(PALETTE_PARAMETERS),
+            Self::CodepointMap => Some(CODEPOINT_MAP_PARAMETERS),
+            Self::FontVariation => Some(FONT_VARIATION_PARAMETERS),
+            Self::Keybind => Some(KEYBIND_PARAMETERS),
+            _ => None,
+        }
+    }
+
+    /// A human readable name of the type.
+    pub fn name(&self) -> String {
+        let name = match self {
+            Self::Bool => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "number",
+            Self::String => "string",
+            Self::Color => "color",
+            Self::Path => "path",
+            Self::Enum(values) => return format!("one of {}", values.join(", ")),
+            Self::Adjustment => "integer or percentage",
+            Self::Palette => "palette entry (N=COLOR)",
+            Self::CodepointMap => "codepoint map (U+XXXX-U+YYYY=fontname)",
+            Self::FontVariation => "font variation (id=value)",
+            Self::FontFeature => "font feature",
+            Self::Keybind => "keybinding (trigger=action:param)",
+            Self::Link => "link (pattern=action)",
+            Self::List => "comma separated list",
+        };
+        name.to_string()
+    }
+
+    /// Which parameter the byte offset `idx` in `value` falls in.
+    pub fn active_parameter(&self, value: &str, idx: usize) -> Option<u32> {
+        let before = value.get(..idx)?;
+        match self {
+            Self::Palette | Self::CodepointMap | Self::FontVariation => {
+                Some(u32::from(before.contains('=')))
+            }
+            Self::Keybind => match before.find('=') {
+                None => Some(0),
+                Some(eq) => Some(if before[eq..].contains(':') { 2 } else { 1 }),
+            },
+            _ => None,
+        }
+    }
+
+    /// The separator written before each parameter after the first.
+    pub fn separators(&self) -> &'static [&'static str] {
+        match self {
+            Self::Keybind => &["=", ":"],
+            Self::Palette | Self::CodepointMap | Self::FontVariation => &["="],
+            _ => &[],
+        }
+    }
+}
+
+/// The platforms a key has an effect on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    MacOS,
+    Linux,
+}
+
+impl Platform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::MacOS => "macOS",
+            Platform::Linux => "Linux",
+        }
+    }
+}
+
+/// A single key in Ghostty's configuration schema.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfigKey {
+    pub name: &'static str,
+    pub kind: ValueKind,
+    /// The value Ghostty uses when the key is not set. `None` means the key
+    /// is unset by default and Ghostty picks a value at runtime.
+    pub default: Option<&'static str>,
+    /// Repeatable keys accumulate values instead of overriding each other.
+    pub repeatable: bool,
+    /// Platforms the key applies to. Empty means every platform.
+    pub platforms: &'static [Platform],
+    /// The release that introduced the key, `None` for keys Ghostty has had
+    /// since 1.0.
+    pub added: Option<Version>,
+    /// The release that dropped the key.
+    pub removed: Option<Version>,
+}
+
+impl ConfigKey {
+    pub(super) const fn new(name: &'static str, kind: ValueKind) -> Self {
+        Self {
+            name,
+            kind,
+            default: None,
+            repeatable: false,
+            platforms: &[],
+            added: None,
+            removed: None,
+        }
+    }
+
+    pub(super) const fn default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub(super) const fn repeatable(mut self) -> Self {
+        self.repeatable = true;
+        self
+    }
+
+    const fn kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    const fn platforms(mut self, platforms: &'static [Platform]) -> Self {
+        self.platforms = platforms;
+        self
+    }
+
+    pub(super) const fn added(mut self, version: Version) -> Self {
+        self.added = Some(version);
+        self
+    }
+
+    pub(super) const fn removed(mut self, version: Version) -> Self {
+        self.removed = Some(version);
+        self
+    }
+
+    /// Whether Ghostty `version` knows the key.
+    pub fn available_in(&self, version: Version) -> bool {
+        self.added.is_none_or(|added| added <= version)
+            && self.removed.is_none_or(|removed| version < removed)
+    }
+
+    /// When the key was added or removed, for keys that were.
+    pub fn version_note(&self) -> Option<String> {
+        match (self.added, self.removed) {
+            (Some(added), Some(removed)) => Some(format!(
+                "Added in Ghostty {added}, removed in Ghostty {removed}"
+            )),
+            (Some(added), None) => Some(format!("Added in Ghostty {added}")),
+            (None, Some(removed)) => Some(format!("Removed in Ghostty {removed}")),
+            (None, None) => None,
+        }
+    }
+}
+
+const MACOS: &[Platform] = &[Platform::MacOS];
+const LINUX: &[Platform] = &[Platform::Linux];
+
+/// The schema: the keys generated from the output of
+/// `ghostty +show-config`, refined with what that output does not tell.
+pub static CONFIG_KEYS: LazyLock<Vec<ConfigKey>> =
+    LazyLock::new(|| GENERATED_KEYS.iter().map(|key| refine(*key)).collect());
+
+pub(super) type Refinement = fn(ConfigKey) -> ConfigKey;
+
+/// What cannot be inferred from a default value: structured values and the
+/// values of enums, keys that repeat without a default, and the platforms a
+/// key applies to.
+pub(super) const REFINEMENTS: &[(&[&str], Refinement)] = &[
+    (
+        &[
+            "font-family",
+            "font-family-bold",
+            "font-family-italic",
+            "font-family-bold-italic",
+        ],
+        |key| key.repeatable(),
+    ),
+    (&["font-feature"], |key| {
+        key.kind(ValueKind::FontFeature).repeatable()
+    }),
+    (
+        &[
+            "font-size",
+            "minimum-contrast",
+            "cursor-opacity",
+            "background-opacity",
+            "mouse-scroll-multiplier",
+            "background-image-opacity",
+        ],
+        |key| key.kind(ValueKind::Float),
+    ),
+    (
+        &[
+            "font-variation",
+            "font-variation-bold",
+            "font-variation-italic",
+            "font-variation-bold-italic",
+        ],
+        |key| key.kind(ValueKind::FontVariation).repeatable(),
+    ),
+    (&["font-codepoint-map"], |key| {
+        key.kind(ValueKind::CodepointMap).repeatable()
+    }),
+    (
+        &[
+            "adjust-cell-width",
+            "adjust-cell-height",
+            "adjust-font-baseline",
+            "adjust-underline-position",
+            "adjust-underline-thickness",
+            "adjust-strikethrough-position",
+            "adjust-strikethrough-thickness",
+        ],
+        |key| key.kind(ValueKind::Adjustment),
+    ),
+    (
+        &[
+            "selection-foreground",
+            "selection-background",
+            "cursor-color",
+            "cursor-text",
+            "unfocused-split-fill",
+            "split-divider-color",
+        ],
+        |key| key.kind(ValueKind::Color),
+    ),
+    (&["palette"], |key| {
+        key.kind(ValueKind::Palette).repeatable()
+    }),
+    (&["cursor-style"], |key| {
+        key.kind(ValueKind::Enum(&["block", "bar", "underline"]))
+    }),
+    (&["cursor-style-blink"], |key| key.kind(ValueKind::Bool)),
+    (&["mouse-shift-capture"], |key| {
+        key.kind(ValueKind::Enum(&["false", "true", "always", "never"]))
+    }),
+    // A boolean or a radius.
+    (&["background-blur"], |key| key.kind(ValueKind::String)),
+    (&["link"], |key| key.kind(ValueKind::Link).repeatable()),
+    (&["working-directory"], |key| key.kind(ValueKind::Path)),
+    (&["keybind"], |key| {
+        key.kind(ValueKind::Keybind).repeatable()
+    }),
+    (&["window-theme"], |key| {
+        key.kind(ValueKind::Enum(&["system", "light", "dark"]))
+    }),
+    (&["clipboard-read", "clipboard-write"], |key| {
+        key.kind(ValueKind::Enum(&["allow", "deny", "ask"]))
+    }),
+    (&["copy-on-select"], |key| {
+        key.kind(ValueKind::Enum(&["true", "false", "clipboard"]))
+    }),
+    (&["config-file", "custom-shader"], |key| {
+        key.kind(ValueKind::Path).repeatable()
+    }),
+    (&["gtk-custom-css", "background-image"], |key| {
+        key.kind(ValueKind::Path)
+    }),
+    (&["shell-integration"], |key| {
+        key.kind(ValueKind::Enum(&["none", "detect", "bash", "fish", "zsh"]))
+    }),
+    (
+        &[
+            "shell-integration-features",
+            "font-synthetic-style",
+            "freetype-load-flags",
+            "app-notifications",
+            "bell-features",
+        ],
+        |key| key.kind(ValueKind::List),
+    ),
+    (&["env"], |key| key.repeatable()),
+    (&["osc-color-report-format"], |key| {
+        key.kind(ValueKind::Enum(&["none", "8-bit", "16-bit"]))
+    }),
+    (&["grapheme-width-method"], |key| {
+        key.kind(ValueKind::Enum(&["legacy", "unicode"]))
+    }),
+    (&["resize-overlay"], |key| {
+        key.kind(ValueKind::Enum(&["always", "never", "after-first"]))
+    }),
+    (&["resize-overlay-position"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "center",
+            "top-left",
+            "top-center",
+            "top-right",
+            "bottom-left",
+            "bottom-center",
+            "bottom-right",
+        ]))
+    }),
+    (&["window-padding-color"], |key| {
+        key.kind(ValueKind::Enum(&["background", "extend", "extend-always"]))
+    }),
+    (&["window-new-tab-position"], |key| {
+        key.kind(ValueKind::Enum(&["current", "end"]))
+    }),
+    (&["quick-terminal-position"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "top", "bottom", "left", "right", "center",
+        ]))
+    }),
+    (&["alpha-blending"], |key| {
+        key.kind(ValueKind::Enum(&["native", "linear", "linear-corrected"]))
+    }),
+    (&["background-image-fit"], |key| {
+        key.kind(ValueKind::Enum(&["contain", "cover", "stretch", "none"]))
+    }),
+    (&["window-subtitle"], |key| {
+        key.kind(ValueKind::Enum(&["false", "working-directory"]))
+    }),
+    (&["macos-non-native-fullscreen"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "false",
+            "true",
+            "visible-menu",
+            "padded-notch",
+        ]))
+        .platforms(MACOS)
+    }),
+    (&["macos-option-as-alt"], |key| {
+        key.kind(ValueKind::Enum(&["false", "true", "left", "right"]))
+            .platforms(MACOS)
+    }),
+    (&["window-colorspace"], |key| {
+        key.kind(ValueKind::Enum(&["srgb", "display-p3"]))
+            .platforms(MACOS)
+    }),
+    (&["window-save-state"], |key| {
+        key.kind(ValueKind::Enum(&["default", "never", "always"]))
+            .platforms(MACOS)
+    }),
+    (&["quick-terminal-screen"], |key| {
+        key.kind(ValueKind::Enum(&["main", "mouse", "macos-menu-bar"]))
+            .platforms(MACOS)
+    }),
+    (&["quick-terminal-space-behavior"], |key| {
+        key.kind(ValueKind::Enum(&["move", "remain"]))
+            .platforms(MACOS)
+    }),
+    (&["macos-titlebar-style"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "native",
+            "transparent",
+            "tabs",
+            "hidden",
+        ]))
+        .platforms(MACOS)
+    }),
+    (
+        &["macos-titlebar-proxy-icon", "macos-window-buttons"],
+        |key| {
+            key.kind(ValueKind::Enum(&["visible", "hidden"]))
+                .platforms(MACOS)
+        },
+    ),
+    (&["macos-icon"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "official",
+            "blueprint",
+            "chalkboard",
+            "microchip",
+            "glass",
+            "holographic",
+            "paper",
+            "retro",
+            "xray",
+            "custom-style",
+        ]))
+        .platforms(MACOS)
+    }),
+    (&["macos-hidden"], |key| {
+        key.kind(ValueKind::Enum(&["never", "always"]))
+            .platforms(MACOS)
+    }),
+    (&["auto-update"], |key| {
+        key.kind(ValueKind::Enum(&["off", "check", "download"]))
+            .platforms(MACOS)
+    }),
+    (&["auto-update-channel"], |key| {
+        key.kind(ValueKind::Enum(&["stable", "tip"]))
+            .platforms(MACOS)
+    }),
+    (
+        &[
+            "font-thicken",
+            "font-thicken-strength",
+            "background-blur-radius",
+            "window-step-resize",
+            "window-vsync",
+            "quit-after-last-window-closed",
+            "macos-window-shadow",
+            "macos-auto-secure-input",
+            "macos-secure-input-indication",
+        ],
+        |key| key.platforms(MACOS),
+    ),
+    (&["gtk-single-instance"], |key| {
+        key.kind(ValueKind::Enum(&["true", "false", "desktop"]))
+            .platforms(LINUX)
+    }),
+    // Since 1.1 the decorations to draw rather than a boolean.
+    (&["window-decoration"], |key| {
+        key.kind(ValueKind::Enum(&["auto", "client", "server", "none"]))
+            .default("auto")
+    }),
+    (&["window-show-tab-bar"], |key| {
+        key.kind(ValueKind::Enum(&["always", "auto", "never"]))
+            .platforms(LINUX)
+    }),
+    (&["linux-cgroup"], |key| {
+        key.kind(ValueKind::Enum(&["never", "always", "single-instance"]))
+            .platforms(LINUX)
+    }),
+    (&["gtk-tabs-location"], |key| {
+        key.kind(ValueKind::Enum(&[
+            "top", "bottom", "left", "right", "hidden",
+        ]))
+        .platforms(LINUX)
+    }),
+    (&["gtk-titlebar-style"], |key| {
+        key.kind(ValueKind::Enum(&["native", "tabs"]))
+            .platforms(LINUX)
+    }),
+    (&["async-backend"], |key| {
+        key.kind(ValueKind::Enum(&["auto", "epoll", "io_uring"]))
+            .platforms(LINUX)
+    }),
+    (
+        &[
+            "class",
+            "x11-instance-name",
+            "gtk-titlebar",
+            "gtk-wide-tabs",
+            "gtk-adwaita",
+            "gtk-titlebar-hide-when-maximized",
+            "linux-cgroup-hard-fail",
+            "quit-after-last-window-closed-delay",
+        ],
+        |key| key.platforms(LINUX),
+    ),
+];
+
+fn refine(key: ConfigKey) -> ConfigKey {
+    REFINEMENTS
+        .iter()
+        .find(|(names, _)| names.contains(&key.name))
+        .map_or(key, |(_, refinement)| refinement(key))
+}
+
+/// Looks up a key in the schema by name.
+pub fn get_config_key(name: &str) -> Option<&'static ConfigKey> {
+    CONFIG_KEYS.iter().find(|key| key.name == name)
+}
@@ -0,0 +1,7 @@
+//! The keys, documentation and releases generated by `build.rs` from the
+//! `ghostty +show-config --default --docs` output of each release in
+//! `schema/`.
+use super::keys::{ConfigKey, ValueKind};
+use super::versions::Version;
+
+include!(concat!(env!("OUT_DIR"), "/schema.rs"));
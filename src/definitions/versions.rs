@@ -0,0 +1,103 @@
+use std::fmt;
+use std::process::Command;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+/// A Ghostty release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    /// Parses `1.1.3`, also accepting a leading `v`, a missing patch number
+    /// and pre-release or build suffixes such as `1.2.0-dev+abc123`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid Ghostty version `{s}`, expected MAJOR.MINOR.PATCH");
+        let core = s.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next().unwrap_or(core);
+        let mut parts = core.split('.').map(|part| part.parse::<u32>());
+        let (Some(Ok(major)), Some(Ok(minor))) = (parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let patch = match parts.next() {
+            None => 0,
+            Some(Ok(patch)) => patch,
+            Some(Err(_)) => return Err(invalid()),
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The version of the `ghostty` on `PATH`, if there is one.
+pub fn detect_version() -> Option<Version> {
+    let output = Command::new("ghostty").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = parse_version_output(&String::from_utf8_lossy(&output.stdout))?;
+    log::info!("Detected Ghostty {version}");
+    Some(version)
+}
+
+/// The version in the output of `ghostty --version`, whose first line reads
+/// `Ghostty 1.1.3`.
+fn parse_version_output(output: &str) -> Option<Version> {
+    let first = output.lines().next()?;
+    first.split_whitespace().find_map(|word| word.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!("1.1.3".parse(), Ok(Version::new(1, 1, 3)));
+        assert_eq!("v1.2".parse(), Ok(Version::new(1, 2, 0)));
+        assert_eq!("1.2.0-dev+0123abc".parse(), Ok(Version::new(1, 2, 0)));
+        assert!("1".parse::<Version>().is_err());
+        assert!("1.x.0".parse::<Version>().is_err());
+        assert!("1.1.1.1".parse::<Version>().is_err());
+        assert_eq!(Version::new(1, 1, 3).to_string(), "1.1.3");
+        assert!(Version::new(1, 0, 1) < Version::new(1, 1, 0));
+    }
+
+    #[test]
+    fn test_parse_version_output() {
+        let output = "Ghostty 1.1.3\n\nVersion\n  - version: 1.1.3\n  - channel: stable\n";
+        assert_eq!(parse_version_output(output), Some(Version::new(1, 1, 3)));
+        assert_eq!(parse_version_output("ghostty: command not found"), None);
+    }
+}
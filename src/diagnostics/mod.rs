@@ -0,0 +1,172 @@
+//! Diagnostics for Ghostty configuration files.
+//!
+//! Every diagnostic carries the name of the rule that produced it as its
+//! `code` so clients can tell the rules apart.
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range, Url};
+
+use crate::codepoints::codepoint_map_diagnostics;
+use crate::definitions::{get_config_key, Platform, Version};
+use crate::fonts::{font_family_diagnostics, font_table_diagnostics};
+use crate::link_regex::link_diagnostics;
+use crate::links::missing_path_diagnostics;
+use crate::migrate::deprecation_diagnostics;
+use crate::parser::ConfigFile;
+use crate::shaders::shader_diagnostics;
+use crate::shell::shell_diagnostics;
+use crate::state::ServerState;
+use crate::working_directory::working_directory_diagnostics;
+
+pub const SOURCE: &str = "ghostty-lsp";
+
+pub fn diagnostics(state: &ServerState, uri: &Url, text: &str) -> Vec<Diagnostic> {
+    if !state.settings.features.diagnostics {
+        return Vec::new();
+    }
+    let config = ConfigFile::parse(text);
+    let mut diagnostics = Vec::new();
+    diagnostics.extend(missing_path_diagnostics(uri, &config));
+    diagnostics.extend(codepoint_map_diagnostics(&config));
+    diagnostics.extend(shell_diagnostics(uri, &config));
+    diagnostics.extend(link_diagnostics(&config));
+    diagnostics.extend(working_directory_diagnostics(uri, &config));
+    diagnostics.extend(shader_diagnostics(&state.docs, uri, &config));
+    if let Some(fonts) = state.fonts.get() {
+        diagnostics.extend(font_family_diagnostics(&config, fonts));
+        diagnostics.extend(font_table_diagnostics(&config, fonts));
+    }
+    diagnostics.extend(platform_diagnostics(&config, state.settings.platform()));
+    diagnostics.extend(version_diagnostics(&config, state.ghostty_version()));
+    diagnostics.extend(deprecation_diagnostics(&config, state.ghostty_version()));
+    state.settings.apply_severity(diagnostics)
+}
+
+/// Hints at keys that have no effect on `platform`.
+fn platform_diagnostics(config: &ConfigFile, platform: Platform) -> Vec<Diagnostic> {
+    config
+        .entries()
+        .filter_map(|entry| {
+            let key = get_config_key(&entry.key)?;
+            if key.platforms.is_empty() || key.platforms.contains(&platform) {
+                return None;
+            }
+            let names: Vec<&str> = key.platforms.iter().map(Platform::name).collect();
+            let mut diagnostic = diagnostic(
+                entry.range(),
+                DiagnosticSeverity::HINT,
+                "unsupported-platform",
+                format!(
+                    "`{}` only has an effect on {}, not {}",
+                    entry.key,
+                    names.join(" and "),
+                    platform.name()
+                ),
+            );
+            diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+            Some(diagnostic)
+        })
+        .collect()
+}
+
+/// An identifier for a set of diagnostics, so that a client pulling them
+/// again can be told nothing changed.
+pub fn result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Warns about keys that `version` of Ghostty no longer or does not yet
+/// know.
+fn version_diagnostics(config: &ConfigFile, version: Version) -> Vec<Diagnostic> {
+    config
+        .entries()
+        .filter_map(|entry| {
+            let key = get_config_key(&entry.key)?;
+            let (code, message) = match (key.added, key.removed) {
+                (_, Some(removed)) if removed <= version => (
+                    "removed-key",
+                    format!(
+                        "`{}` was removed in Ghostty {removed} and has no effect in {version}",
+                        entry.key
+                    ),
+                ),
+                (Some(added), _) if version < added => (
+                    "unsupported-key",
+                    format!(
+                        "`{}` was added in Ghostty {added} and is not supported by {version}",
+                        entry.key
+                    ),
+                ),
+                _ => return None,
+            };
+            Some(diagnostic(
+                entry.key_range,
+                DiagnosticSeverity::WARNING,
+                code,
+                message,
+            ))
+        })
+        .collect()
+}
+
+pub fn diagnostic(
+    range: Range,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_diagnostics() {
+        let config = ConfigFile::parse("font-thicken = true\nfont-size = 13\n");
+        assert!(platform_diagnostics(&config, Platform::MacOS).is_empty());
+        let diagnostics = platform_diagnostics(&config, Platform::Linux);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`font-thicken` only has an effect on macOS, not Linux"
+        );
+        assert_eq!(diagnostics[0].range.start.line, 0);
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    }
+
+    #[test]
+    fn test_version_diagnostics() {
+        let config = ConfigFile::parse(
+            "gtk-adwaita = false
+window-show-tab-bar = never
+",
+        );
+        let messages = |version| -> Vec<String> {
+            version_diagnostics(&config, version)
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect()
+        };
+        assert_eq!(
+            messages(Version::new(1, 0, 1)),
+            vec!["`window-show-tab-bar` was added in Ghostty 1.2.0 and is not supported by 1.0.1"]
+        );
+        assert_eq!(
+            messages(Version::new(1, 2, 0)),
+            vec!["`gtk-adwaita` was removed in Ghostty 1.1.0 and has no effect in 1.2.0"]
+        );
+    }
+}
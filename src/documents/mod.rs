@@ -0,0 +1,47 @@
+//! The set of documents the client has open.
+//!
+//! The text of each document is shared, so cloning the set is cheap enough
+//! to hand a snapshot to every request.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use lsp_types::Url;
+
+#[derive(Debug, Clone, Default)]
+pub struct Documents {
+    docs: HashMap<Url, Arc<str>>,
+}
+
+impl Documents {
+    pub fn open(&mut self, uri: Url, text: String) {
+        self.docs.insert(uri, text.into());
+    }
+
+    pub fn update(&mut self, uri: Url, text: String) {
+        self.docs.insert(uri, text.into());
+    }
+
+    pub fn close(&mut self, uri: &Url) {
+        self.docs.remove(uri);
+    }
+
+    pub fn uris(&self) -> impl Iterator<Item = &Url> {
+        self.docs.keys()
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<&str> {
+        self.docs.get(uri).map(AsRef::as_ref)
+    }
+
+    /// Reads the file at `path`, preferring the open document's unsaved text
+    /// over the contents on disk.
+    pub fn read(&self, path: &Path) -> Option<String> {
+        if let Ok(uri) = Url::from_file_path(path) {
+            if let Some(text) = self.get(&uri) {
+                return Some(text.to_string());
+            }
+        }
+        std::fs::read_to_string(path).ok()
+    }
+}
@@ -0,0 +1,91 @@
+//! Folding ranges for comment blocks, related keys and sections.
+use lsp_types::{FoldingRange, FoldingRangeKind};
+
+use crate::parser::{Block, ConfigFile, LineKind};
+
+pub fn folding_ranges(config: &ConfigFile) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    for block in config.comment_blocks() {
+        push_range(&mut ranges, block, Some(FoldingRangeKind::Comment));
+    }
+    for block in key_groups(config) {
+        push_range(&mut ranges, block, None);
+    }
+    for block in config.sections() {
+        push_range(&mut ranges, block, Some(FoldingRangeKind::Region));
+    }
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges.dedup_by(|a, b| a.start_line == b.start_line && a.end_line == b.end_line);
+    ranges
+}
+
+fn push_range(ranges: &mut Vec<FoldingRange>, block: Block, kind: Option<FoldingRangeKind>) {
+    if block.start == block.end {
+        return;
+    }
+    ranges.push(FoldingRange {
+        start_line: block.start,
+        start_character: None,
+        end_line: block.end,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+    });
+}
+
+/// The group a key belongs to, such as `font` for every `font-*` key.
+pub fn key_group(key: &str) -> &str {
+    key.split('-').next().unwrap_or(key)
+}
+
+/// Runs of consecutive entries whose keys share a group.
+fn key_groups(config: &ConfigFile) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(&str, Block)> = None;
+    for line in &config.lines {
+        let group = match &line.kind {
+            LineKind::Entry(entry) => Some(key_group(&entry.key)),
+            _ => None,
+        };
+        match (&mut current, group) {
+            (Some((name, block)), Some(group)) if *name == group => block.end = line.number,
+            _ => {
+                blocks.extend(current.take().map(|(_, block)| block));
+                current = group.map(|group| {
+                    let block = Block {
+                        start: line.number,
+                        end: line.number,
+                    };
+                    (group, block)
+                });
+            }
+        }
+    }
+    blocks.extend(current.map(|(_, block)| block));
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folding_ranges() {
+        let config = ConfigFile::parse(
+            "# one\n# two\nfont-family = A\nfont-size = 12\ntheme = x\n\nkeybind = a=ignore\nkeybind = b=ignore\n",
+        );
+        let ranges: Vec<_> = folding_ranges(&config)
+            .into_iter()
+            .map(|range| (range.start_line, range.end_line, range.kind))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0, 4, Some(FoldingRangeKind::Region)),
+                (0, 1, Some(FoldingRangeKind::Comment)),
+                (2, 3, None),
+                (6, 7, None),
+            ]
+        );
+    }
+}
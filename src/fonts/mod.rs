@@ -0,0 +1,443 @@
+//! An index of the fonts installed on the system.
+//!
+//! This mirrors `ghostty +list-fonts`: the font directories, including the
+//! ones listed in fontconfig's `fonts.conf`, are scanned and the family and
+//! style names are read from each font's `name` table. Scanning can take a
+//! while, so it happens once on a background thread and the result is
+//! shared through a [`FontCache`]. The clients of one server share their
+//! caches through [`FontCaches`].
+mod tables;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use ttf_parser::{name_id, Face, PlatformId};
+
+use crate::diagnostics::diagnostic;
+use crate::parser::ConfigFile;
+use crate::paths;
+
+pub use tables::{
+    configured_family, feature_description, feature_tag, font_table_diagnostics, FontTables,
+    VariationAxis,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    /// Family names, the preferred (typographic) name first.
+    pub families: Vec<String>,
+    pub style: String,
+    pub path: PathBuf,
+    /// The index of the face within a font collection.
+    pub index: u32,
+}
+
+impl FontFace {
+    pub fn has_family(&self, family: &str) -> bool {
+        self.families
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(family))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontIndex {
+    faces: Vec<FontFace>,
+    /// The tables of each face, read the first time they are needed.
+    tables: Vec<OnceLock<Option<FontTables>>>,
+}
+
+impl FontIndex {
+    pub fn new(faces: Vec<FontFace>) -> Self {
+        let tables = faces.iter().map(|_| OnceLock::new()).collect();
+        Self { faces, tables }
+    }
+
+    /// Reads every font below `dirs`.
+    pub fn scan(dirs: &[PathBuf]) -> Self {
+        let mut files = Vec::new();
+        let mut visited = HashSet::new();
+        for dir in dirs {
+            collect_font_files(dir, &mut visited, &mut files);
+        }
+        files.sort();
+        files.dedup();
+        let faces = files.iter().flat_map(|path| read_faces(path)).collect();
+        Self::new(faces)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// The preferred family names of every installed font, sorted.
+    pub fn families(&self) -> Vec<&str> {
+        let mut families: Vec<&str> = self
+            .faces
+            .iter()
+            .filter_map(|face| face.families.first().map(String::as_str))
+            .collect();
+        families.sort_unstable();
+        families.dedup();
+        families
+    }
+
+    pub fn has_family(&self, family: &str) -> bool {
+        self.faces.iter().any(|face| face.has_family(family))
+    }
+
+    pub fn faces<'a>(&'a self, family: &'a str) -> impl Iterator<Item = &'a FontFace> {
+        self.faces
+            .iter()
+            .filter(move |face| face.has_family(family))
+    }
+
+    /// The tables of each face of `family`. A face's file is only read the
+    /// first time, later calls share the result.
+    pub fn tables<'a>(&'a self, family: &'a str) -> impl Iterator<Item = &'a FontTables> {
+        self.faces
+            .iter()
+            .zip(&self.tables)
+            .filter(move |(face, _)| face.has_family(family))
+            .filter_map(|(face, tables)| tables.get_or_init(|| FontTables::read(face)).as_ref())
+    }
+
+    /// The style names available for `family`, sorted.
+    pub fn styles(&self, family: &str) -> Vec<&str> {
+        let mut styles: Vec<&str> = self
+            .faces
+            .iter()
+            .filter(|face| face.has_family(family))
+            .map(|face| face.style.as_str())
+            .collect();
+        styles.sort_unstable();
+        styles.dedup();
+        styles
+    }
+}
+
+/// A font index that is built on a background thread. Until the scan has
+/// finished, [`FontCache::get`] returns `None` and font-aware features are
+/// skipped, so the caller is told through `on_ready` when it has.
+#[derive(Debug, Clone, Default)]
+pub struct FontCache {
+    index: Arc<OnceLock<FontIndex>>,
+}
+
+impl FontCache {
+    pub fn spawn(dirs: Vec<PathBuf>, on_ready: impl FnOnce() + Send + 'static) -> Self {
+        let cache = Self::default();
+        let index = cache.index.clone();
+        std::thread::spawn(move || {
+            let scanned = FontIndex::scan(&dirs);
+            log::info!("Indexed {} font faces", scanned.faces.len());
+            let _ = index.set(scanned);
+            on_ready();
+        });
+        cache
+    }
+
+    /// A cache that is already populated with `index`.
+    pub fn ready(index: FontIndex) -> Self {
+        let cache = Self::default();
+        let _ = cache.index.set(index);
+        cache
+    }
+
+    pub fn get(&self) -> Option<&FontIndex> {
+        self.index.get()
+    }
+
+    /// Calls `on_ready` on a thread of its own once the index is built.
+    fn when_ready(&self, on_ready: impl FnOnce() + Send + 'static) {
+        let index = self.index.clone();
+        std::thread::spawn(move || {
+            index.wait();
+            on_ready();
+        });
+    }
+}
+
+/// The font caches of every client of a server, one per set of font
+/// directories, so clients that search the same directories share a scan.
+#[derive(Debug, Clone, Default)]
+pub struct FontCaches {
+    caches: Arc<Mutex<HashMap<Vec<PathBuf>, FontCache>>>,
+}
+
+impl FontCaches {
+    /// The cache of `dirs`, scanning them if no client has yet. `on_ready`
+    /// is called once the index is built, like for [`FontCache::spawn`].
+    pub fn get(&self, dirs: Vec<PathBuf>, on_ready: impl FnOnce() + Send + 'static) -> FontCache {
+        let Ok(mut caches) = self.caches.lock() else {
+            return FontCache::spawn(dirs, on_ready);
+        };
+        if let Some(cache) = caches.get(&dirs) {
+            cache.when_ready(on_ready);
+            return cache.clone();
+        }
+        let cache = FontCache::spawn(dirs.clone(), on_ready);
+        caches.insert(dirs, cache.clone());
+        cache
+    }
+}
+
+/// The directories fonts are usually installed in, plus the ones listed in
+/// fontconfig's configuration.
+pub fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+        PathBuf::from("/Library/Fonts"),
+        PathBuf::from("/System/Library/Fonts"),
+    ];
+    if let Some(home) = paths::home_dir() {
+        dirs.push(home.join(".local/share/fonts"));
+        dirs.push(home.join(".fonts"));
+        dirs.push(home.join("Library/Fonts"));
+    }
+
+    let mut confs = vec![PathBuf::from("/etc/fonts/fonts.conf")];
+    if let Some(config_home) = paths::xdg_dir("XDG_CONFIG_HOME", ".config") {
+        confs.push(config_home.join("fontconfig/fonts.conf"));
+    }
+    for conf in confs {
+        if let Ok(text) = std::fs::read_to_string(&conf) {
+            dirs.extend(fontconfig_dirs(&text));
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Extracts the `<dir>` entries of a fontconfig configuration file.
+fn fontconfig_dirs(text: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<dir") {
+        rest = &rest[start + 4..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        // Skip `<directory>` and similar tags as well as self-closing ones.
+        if !(attributes.is_empty() || attributes.starts_with(' ')) || attributes.ends_with('/') {
+            continue;
+        }
+        let Some(end) = rest.find("</dir>") else {
+            break;
+        };
+        let dir = rest[..end].trim();
+        rest = &rest[end..];
+        let dir = if attributes.contains("prefix=\"xdg\"") {
+            match paths::xdg_dir("XDG_DATA_HOME", ".local/share") {
+                Some(data_home) => data_home.join(dir),
+                None => continue,
+            }
+        } else {
+            paths::expand_home(dir)
+        };
+        dirs.push(dir);
+    }
+    dirs
+}
+
+/// Collects the font files below `dir`. Font directories are often
+/// symlinked, so links are followed, and `visited` holds the canonical
+/// directories already read so a link back up the tree is not.
+fn collect_font_files(dir: &Path, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    let Ok(canonical) = dir.canonicalize() else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_font_files(&path, visited, files);
+        } else if is_font_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn is_font_file(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    matches!(extension.as_deref(), Some("ttf" | "otf" | "ttc" | "otc"))
+}
+
+fn read_faces(path: &Path) -> Vec<FontFace> {
+    let Ok(data) = std::fs::read(path) else {
+        return Vec::new();
+    };
+    let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+    (0..count)
+        .filter_map(|index| {
+            let face = Face::parse(&data, index).ok()?;
+            let families: Vec<String> = [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]
+                .into_iter()
+                .filter_map(|id| face_name(&face, id))
+                .fold(Vec::new(), |mut names, name| {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                    names
+                });
+            if families.is_empty() {
+                return None;
+            }
+            let style = face_name(&face, name_id::TYPOGRAPHIC_SUBFAMILY)
+                .or_else(|| face_name(&face, name_id::SUBFAMILY))
+                .unwrap_or_else(|| "Regular".to_string());
+            Some(FontFace {
+                families,
+                style,
+                path: path.to_path_buf(),
+                index,
+            })
+        })
+        .collect()
+}
+
+/// Reads a name from the `name` table, preferring US English.
+fn face_name(face: &Face, id: u16) -> Option<String> {
+    let mut fallback = None;
+    for name in face.names().into_iter().filter(|name| name.name_id == id) {
+        let value = match name.to_string() {
+            Some(value) => value,
+            // Macintosh Roman names are plain ASCII in practice.
+            None if name.platform_id == PlatformId::Macintosh && name.name.is_ascii() => {
+                String::from_utf8_lossy(name.name).to_string()
+            }
+            None => continue,
+        };
+        if name.language() == ttf_parser::Language::English_UnitedStates {
+            return Some(value);
+        }
+        fallback.get_or_insert(value);
+    }
+    fallback
+}
+
+/// Warns about `font-family` values that are not installed. Nothing is
+/// reported when no fonts were found at all, to avoid flagging every family
+/// on systems where fonts live somewhere we do not scan.
+pub fn font_family_diagnostics(config: &ConfigFile, index: &FontIndex) -> Vec<Diagnostic> {
+    if index.is_empty() {
+        return Vec::new();
+    }
+    config
+        .entries()
+        .filter(|entry| entry.key.starts_with("font-family"))
+        .filter(|entry| !entry.value.is_empty() && !index.has_family(&entry.value))
+        .map(|entry| {
+            diagnostic(
+                entry.value_range,
+                DiagnosticSeverity::WARNING,
+                "unknown-font-family",
+                format!("font family `{}` is not installed", entry.value),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(family: &str, style: &str) -> FontFace {
+        FontFace {
+            families: vec![family.to_string()],
+            style: style.to_string(),
+            path: PathBuf::from(format!("/fonts/{family}-{style}.ttf")),
+            index: 0,
+        }
+    }
+
+    #[test]
+    fn test_fontconfig_dirs() {
+        let home = paths::home_dir().unwrap();
+        let dirs = fontconfig_dirs(
+            "<fontconfig>\n<dir>/usr/share/fonts</dir>\n<dir>~/.fonts</dir>\n<cachedir>/var/cache</cachedir>\n</fontconfig>",
+        );
+        assert_eq!(
+            dirs,
+            vec![PathBuf::from("/usr/share/fonts"), home.join(".fonts")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_symlink_loop() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-fonts-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("nested/loop")).unwrap();
+        std::fs::write(dir.join("nested/broken.ttf"), "not a font").unwrap();
+
+        let mut files = Vec::new();
+        collect_font_files(&dir, &mut HashSet::new(), &mut files);
+        assert_eq!(files, vec![dir.join("nested/broken.ttf")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_families_and_styles() {
+        let index = FontIndex::new(vec![
+            face("JetBrains Mono", "Regular"),
+            face("JetBrains Mono", "Bold"),
+            face("Iosevka", "Heavy"),
+        ]);
+        assert_eq!(index.families(), vec!["Iosevka", "JetBrains Mono"]);
+        assert_eq!(index.styles("jetbrains mono"), vec!["Bold", "Regular"]);
+        assert!(index.has_family("iosevka"));
+    }
+
+    #[test]
+    fn test_tables_are_read_once() {
+        let index = FontIndex::new(vec![face("Missing", "Regular"), face("Other", "Regular")]);
+        assert_eq!(index.tables("missing").count(), 0);
+        assert_eq!(index.tables[0].get(), Some(&None));
+        assert_eq!(index.tables[1].get(), None);
+    }
+
+    #[test]
+    fn test_font_caches_share_a_scan() {
+        let dirs = vec![std::env::temp_dir().join("ghostty-lsp-no-fonts")];
+        let caches = FontCaches::default();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let ready = |n: u32| {
+            let sender = sender.clone();
+            move || sender.send(n).unwrap()
+        };
+        let first = caches.get(dirs.clone(), ready(1));
+        let second = caches.get(dirs, ready(2));
+        let mut notified: Vec<u32> = receiver.iter().take(2).collect();
+        notified.sort();
+        assert_eq!(notified, vec![1, 2]);
+        assert!(std::ptr::eq(first.get().unwrap(), second.get().unwrap()));
+    }
+
+    #[test]
+    fn test_unknown_font_family() {
+        let index = FontIndex::new(vec![face("Iosevka", "Regular")]);
+        let config = ConfigFile::parse("font-family = Iosevka\nfont-family-bold = Missing\n");
+        let diagnostics = font_family_diagnostics(&config, &index);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "font family `Missing` is not installed"
+        );
+        assert!(font_family_diagnostics(&config, &FontIndex::default()).is_empty());
+    }
+}
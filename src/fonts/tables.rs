@@ -0,0 +1,191 @@
+//! OpenType feature tags and variation axes read from a font file.
+use std::collections::BTreeSet;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use ttf_parser::Face;
+
+use super::{FontFace, FontIndex};
+use crate::diagnostics::diagnostic;
+use crate::parser::ConfigFile;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariationAxis {
+    pub tag: String,
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontTables {
+    /// Feature tags from the GSUB and GPOS tables, sorted.
+    pub features: BTreeSet<String>,
+    pub axes: Vec<VariationAxis>,
+}
+
+impl FontTables {
+    pub fn read(face: &FontFace) -> Option<Self> {
+        let data = std::fs::read(&face.path).ok()?;
+        let face = Face::parse(&data, face.index).ok()?;
+        let mut tables = Self::default();
+        let layouts = [face.tables().gsub, face.tables().gpos];
+        for layout in layouts.into_iter().flatten() {
+            for feature in layout.features {
+                tables.features.insert(feature.tag.to_string());
+            }
+        }
+        for axis in face.variation_axes() {
+            tables.axes.push(VariationAxis {
+                tag: axis.tag.to_string(),
+                min: axis.min_value,
+                default: axis.def_value,
+                max: axis.max_value,
+            });
+        }
+        Some(tables)
+    }
+
+    /// Merges the tables of every face of `family`.
+    pub fn for_family(index: &FontIndex, family: &str) -> Option<Self> {
+        let mut merged: Option<Self> = None;
+        for tables in index.tables(family) {
+            let merged = merged.get_or_insert_with(Self::default);
+            merged.features.extend(tables.features.iter().cloned());
+            for axis in &tables.axes {
+                if !merged.axes.iter().any(|known| known.tag == axis.tag) {
+                    merged.axes.push(axis.clone());
+                }
+            }
+        }
+        merged
+    }
+}
+
+/// The family that applies to `key`: `font-variation-bold` uses the first
+/// `font-family-bold`, falling back to the first `font-family`. Repeated
+/// `font-family` entries are fallbacks, so the first one is the primary font.
+pub fn configured_family<'a>(config: &'a ConfigFile, key: &str) -> Option<&'a str> {
+    let suffix = ["-bold-italic", "-bold", "-italic"]
+        .into_iter()
+        .find(|suffix| key.ends_with(suffix))
+        .unwrap_or("");
+    let first = |key: &str| {
+        config
+            .entries()
+            .find(|entry| entry.key == key && !entry.value.is_empty())
+            .map(|entry| entry.value.as_str())
+    };
+    first(&format!("font-family{suffix}")).or_else(|| first("font-family"))
+}
+
+/// The feature tag of a `font-feature` value such as `-calt`.
+pub fn feature_tag(value: &str) -> &str {
+    let value = value.trim_start_matches(['-', '+']);
+    value.split('=').next().unwrap_or(value).trim()
+}
+
+/// A short description of well known OpenType features.
+pub fn feature_description(tag: &str) -> Option<String> {
+    let description = match tag {
+        "calt" => "Contextual Alternates",
+        "clig" => "Contextual Ligatures",
+        "dlig" => "Discretionary Ligatures",
+        "liga" => "Standard Ligatures",
+        "hlig" => "Historical Ligatures",
+        "kern" => "Kerning",
+        "zero" => "Slashed Zero",
+        "onum" => "Oldstyle Figures",
+        "lnum" => "Lining Figures",
+        "tnum" => "Tabular Figures",
+        "frac" => "Fractions",
+        "sups" => "Superscript",
+        "subs" => "Subscript",
+        "case" => "Case-Sensitive Forms",
+        "salt" => "Stylistic Alternates",
+        "ccmp" => "Glyph Composition/Decomposition",
+        "mark" => "Mark Positioning",
+        "mkmk" => "Mark to Mark Positioning",
+        "locl" => "Localized Forms",
+        _ => {
+            if let Some(n) = tag.strip_prefix("ss").filter(|n| n.len() == 2) {
+                return Some(format!("Stylistic Set {}", n.trim_start_matches('0')));
+            }
+            if let Some(n) = tag.strip_prefix("cv").filter(|n| n.len() == 2) {
+                return Some(format!("Character Variant {}", n.trim_start_matches('0')));
+            }
+            return None;
+        }
+    };
+    Some(description.to_string())
+}
+
+/// Warns about `font-feature` and `font-variation` values the configured
+/// font does not support.
+pub fn font_table_diagnostics(config: &ConfigFile, index: &FontIndex) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for entry in config.entries() {
+        if entry.value.is_empty() {
+            continue;
+        }
+        let is_feature = entry.key == "font-feature";
+        if !is_feature && !entry.key.starts_with("font-variation") {
+            continue;
+        }
+        let Some(family) = configured_family(config, &entry.key) else {
+            continue;
+        };
+        let Some(tables) = FontTables::for_family(index, family) else {
+            continue;
+        };
+        let message = if is_feature {
+            let tag = feature_tag(&entry.value);
+            (!tables.features.contains(tag))
+                .then(|| format!("font `{family}` does not have the feature `{tag}`"))
+        } else if tables.axes.is_empty() {
+            Some(format!("font `{family}` is not a variable font"))
+        } else {
+            let tag = entry.value.split('=').next().unwrap_or("").trim();
+            (!tables.axes.iter().any(|axis| axis.tag == tag))
+                .then(|| format!("font `{family}` does not have the variation axis `{tag}`"))
+        };
+        if let Some(message) = message {
+            diagnostics.push(diagnostic(
+                entry.value_range,
+                DiagnosticSeverity::WARNING,
+                "unsupported-font-feature",
+                message,
+            ));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_family() {
+        let config = ConfigFile::parse(
+            "font-family = Primary\nfont-family = Fallback\nfont-family-bold = Bold\n",
+        );
+        assert_eq!(
+            configured_family(&config, "font-variation-bold"),
+            Some("Bold")
+        );
+        assert_eq!(
+            configured_family(&config, "font-variation-italic"),
+            Some("Primary")
+        );
+        assert_eq!(configured_family(&config, "font-feature"), Some("Primary"));
+    }
+
+    #[test]
+    fn test_feature_tag_and_description() {
+        assert_eq!(feature_tag("-calt"), "calt");
+        assert_eq!(feature_tag("ss01"), "ss01");
+        assert_eq!(feature_description("ss01").unwrap(), "Stylistic Set 1");
+        assert_eq!(feature_description("liga").unwrap(), "Standard Ligatures");
+        assert_eq!(feature_description("abcd"), None);
+    }
+}
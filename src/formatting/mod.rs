@@ -0,0 +1,241 @@
+//! Formatting of configuration files.
+//!
+//! Entries are written as `key = value` with the value kept exactly as
+//! written, including its quotes. Trailing whitespace is removed and runs of
+//! blank lines are collapsed. The style can be adjusted in the `[format]`
+//! table of a `.ghostty-lsp.toml` next to the configuration file.
+use std::path::Path;
+
+use lsp_types::{Position, Range, TextEdit};
+use serde::Deserialize;
+
+use crate::parser::{utf16_len, ConfigFile, LineKind};
+
+/// The name of the file formatting options are read from.
+pub const OPTIONS_FILE: &str = ".ghostty-lsp.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FormatOptions {
+    /// Pads keys so the `=` of every entry in a section lines up.
+    pub align: bool,
+    /// The most consecutive blank lines to keep between sections.
+    pub max_blank_lines: usize,
+    /// Puts a space between `#` and the text of a comment.
+    pub comment_space: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            align: false,
+            max_blank_lines: 1,
+            comment_space: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OptionsFile {
+    #[serde(default)]
+    format: FormatOptions,
+}
+
+/// Reads the formatting options for the configuration file at `path` from
+/// the `.ghostty-lsp.toml` in the same directory. Defaults are used when
+/// there is no such file.
+pub fn load_options(path: &Path) -> Result<FormatOptions, String> {
+    find_options(path).map(Option::unwrap_or_default)
+}
+
+/// Like [`load_options`], but `None` when there is no options file.
+pub fn find_options(path: &Path) -> Result<Option<FormatOptions>, String> {
+    let Some(options_path) = path.parent().map(|dir| dir.join(OPTIONS_FILE)) else {
+        return Ok(None);
+    };
+    if !options_path.is_file() {
+        return Ok(None);
+    }
+    read_options(&options_path).map(Some)
+}
+
+/// Reads the `[format]` table of the options file at `options_path`, which
+/// unlike [`find_options`] has to exist.
+pub fn read_options(options_path: &Path) -> Result<FormatOptions, String> {
+    let text = std::fs::read_to_string(options_path)
+        .map_err(|err| format!("cannot read `{}`: {err}", options_path.display()))?;
+    parse_options(&text).map_err(|err| format!("{}: {err}", options_path.display()))
+}
+
+fn parse_options(text: &str) -> Result<FormatOptions, String> {
+    toml::from_str::<OptionsFile>(text)
+        .map(|file| file.format)
+        .map_err(|err| err.message().to_string())
+}
+
+/// Formats `text`, always ending it with a single newline unless it is
+/// empty.
+pub fn format(text: &str, options: &FormatOptions) -> String {
+    let config = ConfigFile::parse(text);
+    let mut lines: Vec<String> = Vec::with_capacity(config.lines.len());
+    let mut blank_run = 0;
+    for section in sections(&config) {
+        let width = if options.align {
+            key_width(&config, section.clone())
+        } else {
+            0
+        };
+        for idx in section {
+            let line = &config.lines[idx];
+            if line.kind == LineKind::Blank {
+                blank_run += 1;
+                continue;
+            }
+            // Blank lines are only kept between content, never at the start.
+            if !lines.is_empty() {
+                let keep = blank_run.min(options.max_blank_lines);
+                lines.extend(std::iter::repeat_n(String::new(), keep));
+            }
+            blank_run = 0;
+            lines.push(format_line(&line.text, &line.kind, width, options));
+        }
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut formatted = lines.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+/// Splits the lines into sections, each ending after its blank lines.
+fn sections(config: &ConfigFile) -> Vec<std::ops::Range<usize>> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for (idx, line) in config.lines.iter().enumerate() {
+        let next_is_content = config
+            .lines
+            .get(idx + 1)
+            .is_some_and(|next| next.kind != LineKind::Blank);
+        if line.kind == LineKind::Blank && next_is_content {
+            sections.push(start..idx + 1);
+            start = idx + 1;
+        }
+    }
+    sections.push(start..config.lines.len());
+    sections
+}
+
+/// The width of the longest key among the entries in `lines`.
+fn key_width(config: &ConfigFile, lines: std::ops::Range<usize>) -> usize {
+    config.lines[lines]
+        .iter()
+        .filter_map(|line| match &line.kind {
+            LineKind::Entry(entry) if line.text.contains('=') => Some(entry.key.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn format_line(text: &str, kind: &LineKind, width: usize, options: &FormatOptions) -> String {
+    match kind {
+        LineKind::Blank => String::new(),
+        LineKind::Comment => {
+            let comment = text.trim();
+            let body = comment.trim_start_matches('#');
+            let hashes = &comment[..comment.len() - body.len()];
+            let needs_space = options.comment_space
+                && !body.is_empty()
+                && !body.starts_with(char::is_whitespace)
+                && !body.starts_with('!');
+            if needs_space {
+                format!("{hashes} {body}")
+            } else {
+                comment.to_string()
+            }
+        }
+        LineKind::Entry(entry) => match text.split_once('=') {
+            Some((_, value)) => {
+                let value = value.trim();
+                let key = format!("{:width$}", entry.key);
+                if value.is_empty() {
+                    format!("{key} =")
+                } else {
+                    format!("{key} = {value}")
+                }
+            }
+            // Not a valid entry; keep it for the diagnostics to point at.
+            None => text.trim().to_string(),
+        },
+    }
+}
+
+/// The edits that format `text`: a single edit replacing the whole document,
+/// or none when it is already formatted.
+pub fn formatting_edits(text: &str, options: &FormatOptions) -> Vec<TextEdit> {
+    let formatted = format(text, options);
+    if formatted == text {
+        return Vec::new();
+    }
+    let last_line = text.split('\n').count() - 1;
+    let last = text.rsplit('\n').next().unwrap_or("");
+    let end = Position::new(last_line as u32, utf16_len(last));
+    vec![TextEdit::new(
+        Range::new(Position::new(0, 0), end),
+        formatted,
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_defaults() {
+        let text =
+            "\n\n#comment\nfont-size=12   \n  theme =  \"dark\"\n\n\n\n##  kept\nkeybind =\n\n";
+        assert_eq!(
+            format(text, &FormatOptions::default()),
+            "# comment\nfont-size = 12\ntheme = \"dark\"\n\n##  kept\nkeybind =\n"
+        );
+    }
+
+    #[test]
+    fn test_format_aligned() {
+        let options = FormatOptions {
+            align: true,
+            max_blank_lines: 2,
+            comment_space: false,
+        };
+        let text = "font-size = 12\n#a\nfont-family = Iosevka\n\n\n\nx = 1\nlonger-key = 2\n";
+        assert_eq!(
+            format(text, &options),
+            "font-size   = 12\n#a\nfont-family = Iosevka\n\n\nx          = 1\nlonger-key = 2\n"
+        );
+    }
+
+    #[test]
+    fn test_formatting_edits() {
+        let options = FormatOptions::default();
+        assert!(formatting_edits("a = 1\n", &options).is_empty());
+        let edits = formatting_edits("a=1\nb=2", &options);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.end, Position::new(1, 3));
+        assert_eq!(edits[0].new_text, "a = 1\nb = 2\n");
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(
+            parse_options("[format]\nalign = true\nmax-blank-lines = 0\n"),
+            Ok(FormatOptions {
+                align: true,
+                max_blank_lines: 0,
+                comment_space: true,
+            })
+        );
+        assert_eq!(parse_options(""), Ok(FormatOptions::default()));
+        assert!(parse_options("[format]\nalignment = true\n").is_err());
+    }
+}
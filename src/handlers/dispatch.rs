@@ -0,0 +1,182 @@
+//! Routing of messages to their handlers.
+//!
+//! A malformed message or a panicking handler must not take the server down
+//! with it: requests always get a response, with a JSON-RPC error when they
+//! cannot be handled, and notifications that cannot be handled are logged
+//! and dropped.
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use lsp_server::{ErrorCode, ExtractError, Notification, Request, RequestId, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{cast_notification, cast_request, Refresh};
+use crate::state::ServerState;
+
+/// The text of a panic payload, which is a `&str` or a `String` for panics
+/// raised with a message.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+fn error_response(id: RequestId, code: ErrorCode, message: String) -> Response {
+    let level = match code {
+        ErrorCode::InternalError => log::Level::Error,
+        ErrorCode::InvalidParams => log::Level::Warn,
+        _ => log::Level::Debug,
+    };
+    log::log!(level, "Responding with error: {message}");
+    Response::new_err(id, code as i32, message)
+}
+
+enum Dispatch {
+    Pending(Request),
+    Done(Response),
+}
+
+/// Routes a request to the first handler registered for its method.
+pub(crate) struct RequestDispatcher<'a> {
+    dispatch: Dispatch,
+    state: &'a ServerState,
+}
+
+impl<'a> RequestDispatcher<'a> {
+    pub(crate) fn new(req: Request, state: &'a ServerState) -> Self {
+        Self {
+            dispatch: Dispatch::Pending(req),
+            state,
+        }
+    }
+
+    pub(crate) fn on<R>(mut self, handler: fn(&ServerState, R::Params) -> R::Result) -> Self
+    where
+        R: lsp_types::request::Request,
+        R::Params: DeserializeOwned,
+        R::Result: Serialize,
+    {
+        let req = match self.dispatch {
+            Dispatch::Pending(req) if req.method == R::METHOD => req,
+            dispatch => {
+                self.dispatch = dispatch;
+                return self;
+            }
+        };
+        log::debug!("Got {} request", R::METHOD);
+        let id = req.id.clone();
+        let response = match cast_request::<R>(req) {
+            Ok((id, params)) => {
+                let state = self.state;
+                match catch_unwind(AssertUnwindSafe(|| handler(state, params))) {
+                    Ok(result) => match serde_json::to_value(result) {
+                        Ok(result) => Response {
+                            id,
+                            result: Some(result),
+                            error: None,
+                        },
+                        Err(err) => error_response(
+                            id,
+                            ErrorCode::InternalError,
+                            format!("cannot serialize the {} result: {err}", R::METHOD),
+                        ),
+                    },
+                    Err(panic) => error_response(
+                        id,
+                        ErrorCode::InternalError,
+                        format!(
+                            "{} handler panicked: {}",
+                            R::METHOD,
+                            panic_message(panic.as_ref())
+                        ),
+                    ),
+                }
+            }
+            Err(ExtractError::JsonError { method, error }) => error_response(
+                id,
+                ErrorCode::InvalidParams,
+                format!("invalid params for {method}: {error}"),
+            ),
+            Err(ExtractError::MethodMismatch(req)) => error_response(
+                id,
+                ErrorCode::InternalError,
+                format!("{} was routed to the {} handler", req.method, R::METHOD),
+            ),
+        };
+        self.dispatch = Dispatch::Done(response);
+        self
+    }
+
+    /// The response of the handler that took the request, or a
+    /// `MethodNotFound` error when none did.
+    pub(crate) fn finish(self) -> Response {
+        match self.dispatch {
+            Dispatch::Done(response) => response,
+            Dispatch::Pending(req) => error_response(
+                req.id,
+                ErrorCode::MethodNotFound,
+                format!("unknown request {}", req.method),
+            ),
+        }
+    }
+}
+
+/// Routes a notification to the first handler registered for its method.
+pub(crate) struct NotificationDispatcher<'a> {
+    notif: Option<Notification>,
+    refresh: Option<Refresh>,
+    state: &'a mut ServerState,
+}
+
+impl<'a> NotificationDispatcher<'a> {
+    pub(crate) fn new(notif: Notification, state: &'a mut ServerState) -> Self {
+        Self {
+            notif: Some(notif),
+            refresh: None,
+            state,
+        }
+    }
+
+    pub(crate) fn on<N>(
+        mut self,
+        handler: fn(&mut ServerState, N::Params) -> Option<Refresh>,
+    ) -> Self
+    where
+        N: lsp_types::notification::Notification,
+        N::Params: DeserializeOwned,
+    {
+        let Some(notif) = self.notif.take_if(|notif| notif.method == N::METHOD) else {
+            return self;
+        };
+        log::debug!("Got {} notification", N::METHOD);
+        let params = match cast_notification::<N>(notif) {
+            Ok(params) => params,
+            Err(err) => {
+                log::warn!("Dropping malformed {} notification: {err}", N::METHOD);
+                return self;
+            }
+        };
+        let state = &mut *self.state;
+        match catch_unwind(AssertUnwindSafe(|| handler(state, params))) {
+            Ok(refresh) => self.refresh = refresh,
+            Err(panic) => log::error!(
+                "{} handler panicked: {}",
+                N::METHOD,
+                panic_message(panic.as_ref())
+            ),
+        }
+        self
+    }
+
+    /// The diagnostics the notification made stale, if any. Notifications
+    /// without a handler are ignored.
+    pub(crate) fn finish(self) -> Option<Refresh> {
+        if let Some(notif) = self.notif {
+            log::debug!("Ignoring {} notification", notif.method);
+        }
+        self.refresh
+    }
+}
@@ -1,10 +1,52 @@
+mod dispatch;
+
+use std::collections::HashMap;
+
 use lsp_server::{ExtractError, Notification, Request, RequestId, Response};
 use lsp_types::notification::Notification as NotificationTrait;
-use lsp_types::notification::{DidChangeTextDocument, DidOpenTextDocument};
-use lsp_types::request::{Completion, HoverRequest, Request as RequestTrait};
-use lsp_types::{notification, CompletionResponse, Hover};
+use lsp_types::notification::{
+    DidChangeConfiguration, DidChangeTextDocument, DidChangeWorkspaceFolders, DidCloseTextDocument,
+    DidOpenTextDocument, PublishDiagnostics, SetTrace,
+};
+use lsp_types::request::{
+    CodeActionRequest, Completion, DocumentDiagnosticRequest, DocumentLinkRequest,
+    FoldingRangeRequest, Formatting, HoverRequest, InlayHintRequest, SelectionRangeRequest,
+    SignatureHelpRequest, WorkspaceDiagnosticRequest,
+};
+use lsp_types::{
+    CodeActionOrCommand, CodeActionParams, CodeActionResponse, CompletionParams,
+    CompletionResponse, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+    DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentDiagnosticParams, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
+    DocumentFormattingParams, DocumentLink, DocumentLinkParams, FoldingRange, FoldingRangeParams,
+    FullDocumentDiagnosticReport, Hover, HoverContents, HoverParams, InlayHint, InlayHintParams,
+    MarkupContent, MarkupKind, PublishDiagnosticsParams, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, SelectionRange, SelectionRangeParams, SetTraceParams,
+    SignatureHelp, SignatureHelpParams, TextEdit, UnchangedDocumentDiagnosticReport, Url,
+    WorkspaceDiagnosticParams, WorkspaceDiagnosticReport, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, WorkspaceFolder, WorkspaceFullDocumentDiagnosticReport,
+    WorkspaceUnchangedDocumentDiagnosticReport,
+};
 
-use crate::definitions::get_config_param_description;
+use crate::completion::completions;
+use crate::diagnostics::{diagnostics, result_id};
+use crate::folding::folding_ranges;
+use crate::formatting::{find_options, formatting_edits};
+use crate::hover::hover;
+use crate::inlay_hints::inlay_hints;
+use crate::link_regex::{requested_pattern, test_link, TestLink, TestLinkParams, TestLinkResult};
+use crate::links::{base_dir, document_links};
+use crate::migrate::migrate_actions;
+use crate::parser::ConfigFile;
+use crate::resolver::{
+    resolve_config, theme_dirs, EffectiveConfig, EffectiveConfigParams, EffectiveConfigResult,
+};
+use crate::selection::selection_ranges;
+use crate::settings::Settings;
+use crate::signature_help::signature_help;
+use crate::state::{Background, ServerState};
+use crate::workspace::ghostty_files;
+use dispatch::{NotificationDispatcher, RequestDispatcher};
 
 fn cast_notification<N>(notif: Notification) -> Result<N::Params, ExtractError<Notification>>
 where
@@ -22,100 +64,404 @@ where
     req.extract(R::METHOD)
 }
 
-pub fn handle_notification(notif: Notification, doc: &mut String) {
-    match notif.method.as_str() {
-        DidOpenTextDocument::METHOD => {
-            eprintln!("Got DidOpenTextDocument notification");
-            let params = cast_notification::<notification::DidOpenTextDocument>(notif).unwrap();
-            *doc = params.text_document.text.clone();
-            eprintln!("Got text: {:?}", params.text_document.text.as_str());
+/// Builds the `textDocument/publishDiagnostics` notification for `uri`.
+/// Closed documents get an empty list to clear their diagnostics.
+pub fn publish_diagnostics(state: &ServerState, uri: Url) -> Notification {
+    let diagnostics = state
+        .docs
+        .get(&uri)
+        .map(|text| diagnostics(state, &uri, text))
+        .unwrap_or_default();
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    Notification::new(PublishDiagnostics::METHOD.to_string(), params)
+}
+
+/// The diagnostics to publish after a notification changed a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refresh {
+    /// Publish them right away.
+    Now(Url),
+    /// Publish them once the document stops changing.
+    Debounced(Url),
+    /// Publish those of every open document right away.
+    All,
+}
+
+/// Applies a notification to the state, returning the diagnostics that are
+/// out of date as a result.
+pub fn handle_notification(notif: Notification, state: &mut ServerState) -> Option<Refresh> {
+    NotificationDispatcher::new(notif, state)
+        .on::<DidOpenTextDocument>(did_open)
+        .on::<DidChangeTextDocument>(did_change)
+        .on::<DidCloseTextDocument>(did_close)
+        .on::<SetTrace>(set_trace)
+        .on::<DidChangeConfiguration>(did_change_configuration)
+        .on::<DidChangeWorkspaceFolders>(did_change_workspace_folders)
+        .finish()
+}
+
+/// Applies the result of background work to the state, returning the
+/// diagnostics that are out of date as a result.
+pub fn handle_background(event: Background, state: &mut ServerState) -> Option<Refresh> {
+    match event {
+        Background::FontsIndexed => Some(Refresh::All),
+        Background::VersionDetected(version) => {
+            state.detected_version = version;
+            // The version in the settings wins over the detected one.
+            (state.settings.ghostty_version.is_none() && version.is_some()).then_some(Refresh::All)
         }
-        DidChangeTextDocument::METHOD => {
-            eprintln!("Got DidChangeTextDocument notification");
-            let params = cast_notification::<notification::DidChangeTextDocument>(notif).unwrap();
-            params.content_changes.iter().for_each(|change| {
-                if change.range.is_none() {
-                    eprintln!("No range, updating full text");
-                    *doc = change.text.clone();
-                } else {
-                    // TODO: Implement range updates if we change to
-                    // incremental textDocument updates
-                }
-            });
-            eprintln!("Got params: {params:?}");
+    }
+}
+
+fn did_open(state: &mut ServerState, params: DidOpenTextDocumentParams) -> Option<Refresh> {
+    let uri = params.text_document.uri;
+    state.docs.open(uri.clone(), params.text_document.text);
+    Some(Refresh::Now(uri))
+}
+
+fn did_change(state: &mut ServerState, params: DidChangeTextDocumentParams) -> Option<Refresh> {
+    params.content_changes.iter().for_each(|change| {
+        if change.range.is_none() {
+            log::trace!("No range, updating full text");
+            state
+                .docs
+                .update(params.text_document.uri.clone(), change.text.clone());
+        } else {
+            // TODO: Implement range updates if we change to
+            // incremental textDocument updates
         }
-        _ => {}
+    });
+    Some(Refresh::Debounced(params.text_document.uri))
+}
+
+fn did_close(state: &mut ServerState, params: DidCloseTextDocumentParams) -> Option<Refresh> {
+    state.docs.close(&params.text_document.uri);
+    Some(Refresh::Now(params.text_document.uri))
+}
+
+/// Applies settings pushed by the client. Clients that leave them out
+/// expect the server to pull them with `workspace/configuration`, which the
+/// main loop does.
+fn did_change_configuration(
+    state: &mut ServerState,
+    params: DidChangeConfigurationParams,
+) -> Option<Refresh> {
+    let settings = Settings::from_section(&params.settings)?;
+    state.apply_settings(settings);
+    Some(Refresh::All)
+}
+
+fn did_change_workspace_folders(
+    state: &mut ServerState,
+    params: DidChangeWorkspaceFoldersParams,
+) -> Option<Refresh> {
+    let paths = |folders: Vec<WorkspaceFolder>| {
+        folders
+            .into_iter()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect::<Vec<_>>()
+    };
+    let removed = paths(params.event.removed);
+    state.roots.retain(|root| !removed.contains(root));
+    state.roots.extend(paths(params.event.added));
+    None
+}
+
+fn set_trace(state: &mut ServerState, params: SetTraceParams) -> Option<Refresh> {
+    state.trace = params.value;
+    None
+}
+
+/// Handles a request. Every request gets a response: an error one when the
+/// method is unknown, the params are malformed or the handler panics.
+pub fn handle_request(req: Request, state: &ServerState) -> Response {
+    RequestDispatcher::new(req, state)
+        .on::<Completion>(completion)
+        .on::<HoverRequest>(hover_request)
+        .on::<InlayHintRequest>(inlay_hint)
+        .on::<FoldingRangeRequest>(folding_range)
+        .on::<SelectionRangeRequest>(selection_range)
+        .on::<DocumentLinkRequest>(document_link)
+        .on::<SignatureHelpRequest>(signature_help_request)
+        .on::<Formatting>(formatting)
+        .on::<CodeActionRequest>(code_action)
+        .on::<DocumentDiagnosticRequest>(document_diagnostic)
+        .on::<WorkspaceDiagnosticRequest>(workspace_diagnostic)
+        .on::<TestLink>(test_link_request)
+        .on::<EffectiveConfig>(effective_config)
+        .finish()
+}
+
+fn completion(state: &ServerState, params: CompletionParams) -> Option<CompletionResponse> {
+    if !state.settings.features.completion {
+        return None;
     }
+    let position = params.text_document_position;
+    let uri = &position.text_document.uri;
+    let items = state
+        .docs
+        .get(uri)
+        .map(|text| {
+            let config = ConfigFile::parse(text);
+            let base_dir = base_dir(uri);
+            completions(
+                base_dir.as_deref(),
+                &config,
+                position.position,
+                state.fonts.get(),
+                state.client.snippets,
+                state.ghostty_version(),
+            )
+        })
+        .unwrap_or_default();
+    Some(CompletionResponse::Array(items))
 }
 
-pub fn handle_request(req: Request, doc: &mut String) -> Option<Response> {
-    match req.method.as_str() {
-        Completion::METHOD => {
-            eprintln!("Got completion request");
-            let (id, _params) = cast_request::<Completion>(req).unwrap();
-            let result = Some(CompletionResponse::Array(Vec::new()));
-            let result = serde_json::to_value(result).unwrap();
-            let resp = Response {
-                id,
-                result: Some(result),
-                error: None,
-            };
-            Some(resp)
+fn hover_request(state: &ServerState, params: HoverParams) -> Option<Hover> {
+    if !state.settings.features.hover {
+        return None;
+    }
+    let position = params.text_document_position_params;
+    let hover_contents = state.docs.get(&position.text_document.uri).and_then(|doc| {
+        hover(
+            &position.text_document.uri,
+            &ConfigFile::parse(doc),
+            position.position,
+            state.client.markdown_hover,
+            state.ghostty_version(),
+        )
+    });
+    let cont = match hover_contents {
+        Some(val) => val,
+        None => "No hover contents found".to_string(),
+    };
+    let kind = if state.client.markdown_hover {
+        MarkupKind::Markdown
+    } else {
+        MarkupKind::PlainText
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind, value: cont }),
+        range: None,
+    })
+}
+
+fn inlay_hint(state: &ServerState, params: InlayHintParams) -> Option<Vec<InlayHint>> {
+    if !state.settings.features.inlay_hints {
+        return None;
+    }
+    let uri = params.text_document.uri;
+    state
+        .docs
+        .get(&uri)
+        .map(|text| inlay_hints(&state.docs, &uri, text, params.range))
+}
+
+fn folding_range(state: &ServerState, params: FoldingRangeParams) -> Option<Vec<FoldingRange>> {
+    state
+        .docs
+        .get(&params.text_document.uri)
+        .map(|text| folding_ranges(&ConfigFile::parse(text)))
+}
+
+fn selection_range(
+    state: &ServerState,
+    params: SelectionRangeParams,
+) -> Option<Vec<SelectionRange>> {
+    state
+        .docs
+        .get(&params.text_document.uri)
+        .map(|text| selection_ranges(&ConfigFile::parse(text), &params.positions))
+}
+
+fn document_link(state: &ServerState, params: DocumentLinkParams) -> Option<Vec<DocumentLink>> {
+    if !state.settings.features.document_links {
+        return None;
+    }
+    let uri = params.text_document.uri;
+    state
+        .docs
+        .get(&uri)
+        .map(|text| document_links(&uri, &ConfigFile::parse(text)))
+}
+
+fn signature_help_request(
+    state: &ServerState,
+    params: SignatureHelpParams,
+) -> Option<SignatureHelp> {
+    if !state.settings.features.signature_help {
+        return None;
+    }
+    let position = params.text_document_position_params;
+    state
+        .docs
+        .get(&position.text_document.uri)
+        .and_then(|text| signature_help(&ConfigFile::parse(text), position.position))
+}
+
+fn formatting(state: &ServerState, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+    if !state.settings.features.formatting {
+        return None;
+    }
+    let uri = params.text_document.uri;
+    // An options file next to the configuration wins over the settings.
+    let options = match uri.to_file_path() {
+        Ok(path) => find_options(&path),
+        Err(()) => Ok(None),
+    }
+    .map(|options| {
+        options
+            .or_else(|| state.settings.format.clone())
+            .unwrap_or_default()
+    });
+    // Leave the document alone rather than format it with a style the user
+    // did not ask for.
+    match options {
+        Ok(options) => state
+            .docs
+            .get(&uri)
+            .map(|text| formatting_edits(text, &options)),
+        Err(err) => {
+            log::warn!("Cannot read formatting options: {err}");
+            None
         }
-        HoverRequest::METHOD => {
-            eprintln!("Got hover request");
-            let (id, params) = cast_request::<HoverRequest>(req).unwrap();
-            // read the file at params.text_document_position_params.text_document.uri
-            // and return the contents as a hover
-            let maybe_hover_line =
-                usize::try_from(params.text_document_position_params.position.line);
-
-            let hover_line = match maybe_hover_line {
-                Ok(val) => val,
-                Err(_) => {
-                    eprintln!("Failed to convert hover line to usize");
-                    return None;
-                }
+    }
+}
+
+fn code_action(state: &ServerState, params: CodeActionParams) -> Option<CodeActionResponse> {
+    if !state.settings.features.code_actions {
+        return None;
+    }
+    let uri = params.text_document.uri;
+    let text = state.docs.get(&uri)?;
+    let mut actions = migrate_actions(
+        &uri,
+        &ConfigFile::parse(text),
+        state.ghostty_version(),
+        params.range,
+        &params.context.diagnostics,
+    );
+    // Clients ask for some kinds only, such as `source` actions on save.
+    if let Some(only) = params.context.only {
+        actions.retain(|action| {
+            let CodeActionOrCommand::CodeAction(action) = action else {
+                return false;
             };
+            let kind = action.kind.as_ref().map_or("", |kind| kind.as_str());
+            only.iter().any(|only| {
+                kind == only.as_str() || kind.starts_with(&format!("{}.", only.as_str()))
+            })
+        });
+    }
+    Some(actions)
+}
 
-            let mut hover_contents: Option<String> = None;
-            for (line_num, line) in doc.lines().enumerate() {
-                if line_num == hover_line && line.find('=').is_some() {
-                    let param_name = line.split('=').next().unwrap().trim();
-                    eprintln!("Found param name: {:?}", param_name);
-                    let param_desc = get_config_param_description(param_name);
-                    hover_contents = Some(param_desc)
-                }
-            }
+/// Answers a diagnostics pull from clients that prefer it over
+/// `textDocument/publishDiagnostics`, with an unchanged report when the
+/// diagnostics are the ones the client already has.
+fn document_diagnostic(
+    state: &ServerState,
+    params: DocumentDiagnosticParams,
+) -> DocumentDiagnosticReportResult {
+    let uri = params.text_document.uri;
+    let items = state
+        .docs
+        .get(&uri)
+        .map(|text| diagnostics(state, &uri, text))
+        .unwrap_or_default();
+    let result_id = result_id(&items);
+    let report = if params.previous_result_id.as_ref() == Some(&result_id) {
+        DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+            related_documents: None,
+            unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
+        })
+    } else {
+        DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+            related_documents: None,
+            full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items,
+            },
+        })
+    };
+    DocumentDiagnosticReportResult::Report(report)
+}
 
-            let cont = match hover_contents {
-                Some(val) => val,
-                None => "No hover contents found".to_string(),
-            };
-            let hover = Hover {
-                contents: lsp_types::HoverContents::Scalar(lsp_types::MarkedString::String(
-                    cont.to_string(),
-                )),
-                range: None,
-            };
-            let result = serde_json::to_value(hover).unwrap();
-            eprintln!("Sending hover response: {result:?}");
-            let resp = Response {
-                id,
-                result: Some(result),
-                error: None,
-            };
-            Some(resp)
-        }
-        _ => None,
+/// The diagnostics of every Ghostty file in the workspace, read from disk
+/// unless the file is open.
+fn workspace_diagnostic(
+    state: &ServerState,
+    params: WorkspaceDiagnosticParams,
+) -> WorkspaceDiagnosticReportResult {
+    let previous: HashMap<Url, String> = params
+        .previous_result_ids
+        .into_iter()
+        .map(|previous| (previous.uri, previous.value))
+        .collect();
+    let items = ghostty_files(&state.roots, &state.docs)
+        .into_iter()
+        .filter_map(|path| {
+            let uri = Url::from_file_path(&path).ok()?;
+            let text = state.docs.read(&path)?;
+            let items = diagnostics(state, &uri, &text);
+            let result_id = result_id(&items);
+            Some(if previous.get(&uri) == Some(&result_id) {
+                WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id,
+                        },
+                    },
+                )
+            } else {
+                WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                    uri,
+                    version: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: Some(result_id),
+                        items,
+                    },
+                })
+            })
+        })
+        .collect();
+    WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })
+}
+
+fn test_link_request(state: &ServerState, params: TestLinkParams) -> TestLinkResult {
+    let config = params
+        .text_document
+        .as_ref()
+        .and_then(|document| state.docs.get(&document.uri))
+        .map(ConfigFile::parse);
+    match requested_pattern(&params, config.as_ref()) {
+        Some(pattern) => test_link(&pattern, &params.text),
+        None => TestLinkResult {
+            error: Some("no `link` pattern at the given position".to_string()),
+            ..Default::default()
+        },
     }
 }
 
+fn effective_config(state: &ServerState, params: EffectiveConfigParams) -> EffectiveConfigResult {
+    let uri = params.text_document.uri;
+    let values = match (state.docs.get(&uri), uri.to_file_path()) {
+        (Some(text), Ok(path)) => {
+            let theme_dirs = state.settings.theme_dirs(theme_dirs());
+            resolve_config(&state.docs, &path, &ConfigFile::parse(text), &theme_dirs)
+        }
+        _ => Vec::new(),
+    };
+    EffectiveConfigResult { values }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lsp_server::Notification;
-    use lsp_types::notification;
+    use lsp_server::{ErrorCode, Notification};
+    use lsp_types::{notification, Url};
 
     #[test]
     fn test_cast_did_open_notification() {
@@ -173,7 +519,8 @@ mod tests {
 
     #[test]
     fn test_handle_did_open_document_and_did_change_just_text() {
-        let mut doc = String::new();
+        let mut state = ServerState::default();
+        let uri = Url::parse("file:///home/alex/Projects/ghostty/src/main.rs").unwrap();
         let notif = Notification {
             method: "textDocument/didOpen".to_string(),
             params: serde_json::json!({
@@ -185,8 +532,8 @@ mod tests {
                 }
             }),
         };
-        handle_notification(notif, &mut doc);
-        assert_eq!(doc, "hello world\n");
+        handle_notification(notif, &mut state);
+        assert_eq!(state.docs.get(&uri).unwrap(), "hello world\n");
         let notif = Notification {
             method: "textDocument/didChange".to_string(),
             params: serde_json::json!({
@@ -201,13 +548,14 @@ mod tests {
                 ]
             }),
         };
-        handle_notification(notif, &mut doc);
-        assert_eq!(doc, "h");
+        handle_notification(notif, &mut state);
+        assert_eq!(state.docs.get(&uri).unwrap(), "h");
     }
 
     #[test]
     fn test_single_line_handle_did_open_and_did_change() {
-        let mut doc = String::new();
+        let mut state = ServerState::default();
+        let uri = Url::parse("file:///home/alex/Projects/ghostty/src/main.rs").unwrap();
         let notif = Notification {
             method: "textDocument/didOpen".to_string(),
             params: serde_json::json!({
@@ -219,8 +567,8 @@ mod tests {
                 }
             }),
         };
-        handle_notification(notif, &mut doc);
-        assert_eq!(doc, "hello");
+        handle_notification(notif, &mut state);
+        assert_eq!(state.docs.get(&uri).unwrap(), "hello");
         let notif = Notification {
             method: "textDocument/didChange".to_string(),
             params: serde_json::json!({
@@ -235,7 +583,142 @@ mod tests {
                 ]
             }),
         };
-        handle_notification(notif, &mut doc);
-        assert_eq!(doc, "h");
+        handle_notification(notif, &mut state);
+        assert_eq!(state.docs.get(&uri).unwrap(), "h");
+    }
+
+    fn request(method: &str, params: serde_json::Value) -> Request {
+        Request::new(RequestId::from(1), method.to_string(), params)
+    }
+
+    fn error_code(response: &Response) -> Option<i32> {
+        response.error.as_ref().map(|error| error.code)
+    }
+
+    #[test]
+    fn test_cast_malformed_payloads() {
+        let notif = Notification {
+            method: "textDocument/didOpen".to_string(),
+            params: serde_json::json!({ "textDocument": { "uri": "not a uri" } }),
+        };
+        assert!(matches!(
+            cast_notification::<notification::DidOpenTextDocument>(notif),
+            Err(ExtractError::JsonError { .. })
+        ));
+        let req = request("textDocument/hover", serde_json::json!([1, 2]));
+        assert!(matches!(
+            cast_request::<HoverRequest>(req),
+            Err(ExtractError::JsonError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_request_errors() {
+        let state = ServerState::default();
+        let response = handle_request(
+            request("textDocument/unknown", serde_json::json!({})),
+            &state,
+        );
+        assert_eq!(
+            error_code(&response),
+            Some(ErrorCode::MethodNotFound as i32)
+        );
+        assert_eq!(response.id, RequestId::from(1));
+
+        let response = handle_request(
+            request(
+                "textDocument/hover",
+                serde_json::json!({ "position": "start" }),
+            ),
+            &state,
+        );
+        assert_eq!(error_code(&response), Some(ErrorCode::InvalidParams as i32));
+
+        let response = handle_request(
+            request(
+                "textDocument/foldingRange",
+                serde_json::json!({ "textDocument": { "uri": "file:///closed" } }),
+            ),
+            &state,
+        );
+        assert_eq!(error_code(&response), None);
+        assert_eq!(response.result, Some(serde_json::Value::Null));
+    }
+
+    #[test]
+    fn test_document_diagnostic_unchanged() {
+        let mut state = ServerState::default();
+        let uri = Url::parse("file:///nonexistent/config").unwrap();
+        state
+            .docs
+            .open(uri.clone(), "font-thicken = true\n".to_string());
+        let pull = |previous_result_id: Option<String>| {
+            let response = handle_request(
+                request(
+                    "textDocument/diagnostic",
+                    serde_json::json!({
+                        "textDocument": { "uri": uri },
+                        "previousResultId": previous_result_id,
+                    }),
+                ),
+                &state,
+            );
+            response.result.unwrap()
+        };
+        let full = pull(None);
+        assert_eq!(full["kind"], "full");
+        let result_id = full["resultId"].as_str().unwrap().to_string();
+        let unchanged = pull(Some(result_id.clone()));
+        assert_eq!(
+            unchanged,
+            serde_json::json!({ "kind": "unchanged", "resultId": result_id })
+        );
+        assert_eq!(pull(Some("stale".to_string()))["kind"], "full");
+    }
+
+    #[test]
+    fn test_handler_panic_is_an_error_response() {
+        let state = ServerState::default();
+        let req = request(
+            "textDocument/foldingRange",
+            serde_json::json!({ "textDocument": { "uri": "file:///config" } }),
+        );
+        let response = RequestDispatcher::new(req, &state)
+            .on::<FoldingRangeRequest>(|_, _| panic!("boom"))
+            .finish();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, ErrorCode::InternalError as i32);
+        assert!(error.message.ends_with("handler panicked: boom"));
+    }
+
+    #[test]
+    fn test_malformed_notification_is_dropped() {
+        let mut state = ServerState::default();
+        let notif = Notification {
+            method: "textDocument/didChange".to_string(),
+            params: serde_json::json!({ "contentChanges": "h" }),
+        };
+        assert!(handle_notification(notif, &mut state).is_none());
+        let notif = Notification {
+            method: "$/unknown".to_string(),
+            params: serde_json::Value::Null,
+        };
+        assert!(handle_notification(notif, &mut state).is_none());
+    }
+
+    #[test]
+    fn test_detected_version_refreshes_diagnostics() {
+        use crate::definitions::Version;
+
+        let mut state = ServerState::default();
+        let version = Some(Version::new(1, 1, 0));
+        let refresh = handle_background(Background::VersionDetected(version), &mut state);
+        assert_eq!(refresh, Some(Refresh::All));
+        assert_eq!(state.ghostty_version(), Version::new(1, 1, 0));
+
+        state.settings.ghostty_version = Some(Version::new(1, 0, 0));
+        let refresh = handle_background(Background::VersionDetected(version), &mut state);
+        assert_eq!(refresh, None);
+        assert_eq!(state.ghostty_version(), Version::new(1, 0, 0));
     }
 }
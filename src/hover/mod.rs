@@ -0,0 +1,97 @@
+//! Hover documentation for configuration entries.
+use lsp_types::{Position, Url};
+
+use crate::codepoints::codepoint_range_hover;
+use crate::definitions::{
+    get_config_key, get_config_param_description, key_documentation, Version,
+};
+use crate::parser::ConfigFile;
+use crate::shell::command_hover;
+
+/// The hover text for the entry at `position`, with details about the value
+/// under the cursor where there are any and when the key was added or
+/// removed relative to Ghostty `version`. It is markdown when `markdown` is
+/// set and plain text otherwise.
+pub fn hover(
+    uri: &Url,
+    config: &ConfigFile,
+    position: Position,
+    markdown: bool,
+    version: Version,
+) -> Option<String> {
+    let entry = config.entry_at_line(position.line)?;
+    log::trace!("Found param name: {:?}", entry.key);
+    let details = match entry.key.as_str() {
+        "font-codepoint-map" => entry
+            .value_offset(position.character)
+            .and_then(|idx| codepoint_range_hover(entry, idx)),
+        "command" => command_hover(uri, entry),
+        _ => None,
+    };
+    let versions = get_config_key(&entry.key).and_then(|key| {
+        let note = key.version_note()?;
+        Some(if key.available_in(version) {
+            note
+        } else {
+            format!("{note}. Not available in Ghostty {version}.")
+        })
+    });
+    if !markdown {
+        let mut text = get_config_param_description(&entry.key);
+        if let Some(versions) = versions {
+            text = format!("{versions}\n\n{text}");
+        }
+        return match details {
+            Some(details) => Some(format!("{details}\n\n{text}")),
+            None => Some(text),
+        };
+    }
+    let mut text = format!("**`{}`**", entry.key);
+    if let Some(details) = details {
+        // Keep the line breaks of the details, which markdown would join.
+        text.push_str(&format!("\n\n{}", details.replace('\n', "  \n")));
+    }
+    if let Some(versions) = versions {
+        text.push_str(&format!("\n\n*{versions}*"));
+    }
+    if let Some(documentation) = key_documentation(&entry.key) {
+        // The prose is plain text, so keep its line breaks and indentation
+        // as they are.
+        text.push_str(&format!("\n\n```text\n{documentation}\n```"));
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::LATEST;
+
+    #[test]
+    fn test_hover_formats() {
+        let uri = Url::parse("file:///config").unwrap();
+        let config = ConfigFile::parse("font-codepoint-map = U+E000=Symbols\n");
+        let position = Position::new(0, 22);
+        let plain = hover(&uri, &config, position, false, LATEST).unwrap();
+        assert!(plain.starts_with("U+E000: 1 codepoint\nBlocks: Private Use Area\n\n"));
+        let markdown = hover(&uri, &config, position, true, LATEST).unwrap();
+        assert!(markdown.starts_with(
+            "**`font-codepoint-map`**\n\nU+E000: 1 codepoint  \nBlocks: Private Use Area\n\n```text\n"
+        ));
+        assert!(markdown.ends_with("\n```"));
+    }
+
+    #[test]
+    fn test_hover_version_notes() {
+        let uri = Url::parse("file:///config").unwrap();
+        let config = ConfigFile::parse(
+            "gtk-adwaita = true
+",
+        );
+        let position = Position::new(0, 2);
+        let plain = hover(&uri, &config, position, false, LATEST).unwrap();
+        assert!(plain.starts_with("Removed in Ghostty 1.1.0. Not available in Ghostty 1.2.0.\n\n"));
+        let markdown = hover(&uri, &config, position, true, Version::new(1, 0, 1)).unwrap();
+        assert!(markdown.starts_with("**`gtk-adwaita`**\n\n*Removed in Ghostty 1.1.0*\n\n"));
+    }
+}
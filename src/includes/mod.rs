@@ -0,0 +1,100 @@
+//! Following `config-file` includes.
+//!
+//! Ghostty loads `config-file` entries after the file that contains them has
+//! been fully read, in the order they were declared. Files included by an
+//! included file are queued behind the ones already pending, so every value
+//! in an included file overrides the values of the file that included it.
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::documents::Documents;
+use crate::parser::ConfigFile;
+use crate::paths;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludedFile {
+    pub path: PathBuf,
+    pub config: ConfigFile,
+}
+
+/// Resolves the value of a `config-file` entry relative to the directory of
+/// the file containing it. A leading `?` marks the file as optional.
+pub fn resolve_config_file(base_dir: &Path, value: &str) -> (PathBuf, bool) {
+    let (value, optional) = match value.strip_prefix('?') {
+        Some(rest) => (rest, true),
+        None => (value, false),
+    };
+    (paths::resolve(base_dir, value), optional)
+}
+
+/// Returns every file included from `config`, directly or transitively, in
+/// the order Ghostty loads them. Missing files and cycles are skipped.
+pub fn collect_includes(docs: &Documents, path: &Path, config: &ConfigFile) -> Vec<IncludedFile> {
+    let mut seen = HashSet::new();
+    seen.insert(canonical(path));
+
+    let mut queue = VecDeque::new();
+    queue_includes(path, config, &mut queue);
+
+    let mut included = Vec::new();
+    while let Some(path) = queue.pop_front() {
+        if !seen.insert(canonical(&path)) {
+            continue;
+        }
+        let Some(text) = docs.read(&path) else {
+            continue;
+        };
+        let config = ConfigFile::parse(&text);
+        queue_includes(&path, &config, &mut queue);
+        included.push(IncludedFile { path, config });
+    }
+    included
+}
+
+fn queue_includes(path: &Path, config: &ConfigFile, queue: &mut VecDeque<PathBuf>) {
+    let base_dir = path.parent().unwrap_or(Path::new("/"));
+    for entry in config.entries().filter(|e| e.key == "config-file") {
+        if entry.value.is_empty() {
+            continue;
+        }
+        let (path, _optional) = resolve_config_file(base_dir, &entry.value);
+        queue.push_back(path);
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_optional_config_file() {
+        let (path, optional) = resolve_config_file(Path::new("/cfg"), "?extra");
+        assert_eq!(path, PathBuf::from("/cfg/extra"));
+        assert!(optional);
+    }
+
+    #[test]
+    fn test_collect_includes_follows_open_documents_and_skips_cycles() {
+        let mut docs = Documents::default();
+        let root = Path::new("/virtual/config");
+        docs.open(
+            lsp_types::Url::from_file_path("/virtual/a").unwrap(),
+            "config-file = b\nconfig-file = config\n".to_string(),
+        );
+        docs.open(
+            lsp_types::Url::from_file_path("/virtual/b").unwrap(),
+            "font-size = 10\n".to_string(),
+        );
+        let config = ConfigFile::parse("config-file = a\n");
+        let included = collect_includes(&docs, root, &config);
+        let paths: Vec<_> = included.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/virtual/a"), PathBuf::from("/virtual/b")]
+        );
+    }
+}
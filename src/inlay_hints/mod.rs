@@ -0,0 +1,291 @@
+//! Inlay hints shown after configuration values.
+use std::collections::HashMap;
+use std::path::Path;
+
+use lsp_types::{InlayHint, InlayHintLabel, Position, Range, Url};
+
+use crate::definitions::{get_config_key, ValueKind};
+use crate::documents::Documents;
+use crate::includes::collect_includes;
+use crate::parser::{ConfigFile, Entry};
+
+/// Names of the first 16 palette entries.
+pub const ANSI_COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// Where a later value for a key was set.
+#[derive(Debug, Clone, PartialEq)]
+enum Override {
+    SameFile(u32),
+    Included(String, u32),
+}
+
+impl Override {
+    fn label(&self) -> String {
+        match self {
+            Override::SameFile(line) => format!("(overridden at line {})", line + 1),
+            Override::Included(file, line) => {
+                format!("(overridden at line {} of {file})", line + 1)
+            }
+        }
+    }
+}
+
+pub fn inlay_hints(docs: &Documents, uri: &Url, text: &str, range: Range) -> Vec<InlayHint> {
+    let config = ConfigFile::parse(text);
+    let overrides = find_overrides(docs, uri, &config);
+    let font_size = effective_font_size(&config);
+
+    let mut hints = Vec::new();
+    for entry in config.entries() {
+        if entry.line < range.start.line || entry.line > range.end.line {
+            continue;
+        }
+        if let Some(location) = overrides.get(&entry.line) {
+            hints.push(hint(entry.key_range.end, location.label()));
+        }
+        if let Some(label) = value_hint(entry, font_size) {
+            hints.push(hint(entry.value_range.end, label));
+        }
+    }
+    hints
+}
+
+fn hint(position: Position, label: String) -> InlayHint {
+    InlayHint {
+        position,
+        label: InlayHintLabel::String(label),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
+
+/// Maps the line of every entry that is overridden later on to the place it
+/// is overridden.
+fn find_overrides(docs: &Documents, uri: &Url, config: &ConfigFile) -> HashMap<u32, Override> {
+    let mut last_set: HashMap<&str, Override> = HashMap::new();
+    if let Ok(path) = uri.to_file_path() {
+        // Ghostty uses the last value it reads, so walk everything
+        // backwards and keep the first occurrence found.
+        for included in collect_includes(docs, &path, config).iter().rev() {
+            let name = file_name(&included.path);
+            let entries: Vec<&Entry> = included.config.entries().collect();
+            for entry in entries.into_iter().rev() {
+                if let Some(key) = overridable_key(entry) {
+                    last_set
+                        .entry(key)
+                        .or_insert_with(|| Override::Included(name.clone(), entry.line));
+                }
+            }
+        }
+    }
+
+    let mut overrides = HashMap::new();
+    let entries: Vec<&Entry> = config.entries().collect();
+    for entry in entries.iter().rev() {
+        let Some(key) = overridable_key(entry) else {
+            continue;
+        };
+        if let Some(location) = last_set.get(key) {
+            overrides.insert(entry.line, location.clone());
+        }
+        last_set.insert(key, Override::SameFile(entry.line));
+    }
+    overrides
+}
+
+/// The schema name of the entry's key, unless the key is unknown or
+/// repeatable, in which case values accumulate instead of overriding.
+fn overridable_key(entry: &Entry) -> Option<&'static str> {
+    get_config_key(&entry.key)
+        .filter(|key| !key.repeatable)
+        .map(|key| key.name)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn value_hint(entry: &Entry, font_size: f64) -> Option<String> {
+    let key = get_config_key(&entry.key)?;
+    match key.kind {
+        ValueKind::Palette => {
+            let index: usize = entry.value.split('=').next()?.trim().parse().ok()?;
+            ANSI_COLOR_NAMES.get(index).map(|name| name.to_string())
+        }
+        ValueKind::Adjustment => adjustment_delta(&entry.key, &entry.value, font_size),
+        _ => {
+            let default = key.default?;
+            if entry.value.is_empty() || same_value(key.kind, &entry.value, default) {
+                None
+            } else {
+                Some(format!("(default: {default})"))
+            }
+        }
+    }
+}
+
+fn same_value(kind: ValueKind, value: &str, default: &str) -> bool {
+    match kind {
+        ValueKind::Integer | ValueKind::Float => {
+            match (value.parse::<f64>(), default.parse::<f64>()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => value == default,
+            }
+        }
+        _ => value.eq_ignore_ascii_case(default),
+    }
+}
+
+/// The font size the file ends up with, used to estimate cell metrics.
+fn effective_font_size(config: &ConfigFile) -> f64 {
+    config
+        .entries()
+        .filter(|entry| entry.key == "font-size")
+        .filter_map(|entry| entry.value.parse().ok())
+        .last()
+        .unwrap_or(12.0)
+}
+
+/// Estimates the change in points from a percentage adjustment. Only the
+/// cell size can be estimated without reading the font, using the typical
+/// monospace proportions of a cell width of 0.6em and height of 1.2em.
+fn adjustment_delta(key: &str, value: &str, font_size: f64) -> Option<String> {
+    let percent: f64 = value.strip_suffix('%')?.trim().parse().ok()?;
+    let base = match key {
+        "adjust-cell-width" => font_size * 0.6,
+        "adjust-cell-height" => font_size * 1.2,
+        _ => return None,
+    };
+    let delta = base * percent / 100.0;
+    Some(format!("(≈ {delta:+.1}pt)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(text: &str) -> Vec<(u32, String)> {
+        let docs = Documents::default();
+        let uri = Url::parse("file:///virtual/config").unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+        inlay_hints(&docs, &uri, text, range)
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position.line, label),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_default_hint() {
+        assert_eq!(
+            labels("font-size = 14\nfont-size-x = 1\nwindow-padding-x = 2.0\n"),
+            vec![(0, "(default: 12)".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_overridden_hint() {
+        let hints = labels("background = #000000\nforeground = #ffffff\nbackground = #111111\n");
+        assert_eq!(
+            hints,
+            vec![
+                (0, "(overridden at line 3)".to_string()),
+                (0, "(default: #282c34)".to_string()),
+                (2, "(default: #282c34)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeatable_keys_are_not_overridden() {
+        assert!(labels("keybind = ctrl+a=ignore\nkeybind = ctrl+b=ignore\n").is_empty());
+    }
+
+    #[test]
+    fn test_palette_hint() {
+        assert_eq!(
+            labels("palette = 4=#0000ff\npalette = 200=#ffffff\n"),
+            vec![(0, "blue".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_adjustment_hint() {
+        assert_eq!(
+            labels("font-size = 10\nadjust-cell-height = 25%\nadjust-font-baseline = 10%\n"),
+            vec![
+                (0, "(default: 12)".to_string()),
+                (1, "(≈ +3.0pt)".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overridden_in_included_file() {
+        let mut docs = Documents::default();
+        docs.open(
+            Url::from_file_path("/virtual/extra").unwrap(),
+            "\ntheme = dark\n".to_string(),
+        );
+        let uri = Url::from_file_path("/virtual/config").unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(10, 0));
+        let hints = inlay_hints(&docs, &uri, "theme = light\nconfig-file = extra\n", range);
+        assert_eq!(hints.len(), 1);
+        assert!(matches!(
+            &hints[0].label,
+            InlayHintLabel::String(label) if label == "(overridden at line 2 of extra)"
+        ));
+    }
+
+    #[test]
+    fn test_overridden_twice_in_included_file() {
+        let mut docs = Documents::default();
+        docs.open(
+            Url::from_file_path("/virtual/twice").unwrap(),
+            "theme = dark
+theme = light
+"
+            .to_string(),
+        );
+        let uri = Url::from_file_path("/virtual/config").unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(10, 0));
+        let hints = inlay_hints(
+            &docs,
+            &uri,
+            "theme = light
+config-file = twice
+",
+            range,
+        );
+        assert_eq!(hints.len(), 1);
+        assert!(matches!(
+            &hints[0].label,
+            InlayHintLabel::String(label) if label == "(overridden at line 2 of twice)"
+        ));
+    }
+}
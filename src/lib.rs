@@ -1,2 +1,31 @@
+pub mod capabilities;
+pub mod cli;
+pub mod codepoints;
+pub mod completion;
 pub mod definitions;
+pub mod diagnostics;
+pub mod documents;
+pub mod folding;
+pub mod fonts;
+pub mod formatting;
 pub mod handlers;
+pub mod hover;
+pub mod includes;
+pub mod inlay_hints;
+pub mod link_regex;
+pub mod links;
+pub mod logging;
+pub mod migrate;
+pub mod parser;
+pub mod paths;
+pub mod resolver;
+pub mod selection;
+pub mod settings;
+pub mod shaders;
+pub mod shell;
+pub mod signature_help;
+pub mod state;
+pub mod transport;
+pub mod worker;
+pub mod working_directory;
+pub mod workspace;
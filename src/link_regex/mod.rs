@@ -0,0 +1,847 @@
+//! Validation and match previews for `link` regular expressions.
+//!
+//! Ghostty compiles `link` patterns with Oniguruma. The checker here covers
+//! the commonly used subset of its syntax so mistakes are reported in the
+//! editor, with the same wording Oniguruma uses, instead of in Ghostty's
+//! log.
+//!
+//! A `link` value is a pattern and the action to take when a match is
+//! clicked, written `PATTERN=ACTION`. Patterns can contain `=` themselves,
+//! so the action is what follows the last `=` that is followed by an
+//! action name. Without one, the whole value is the pattern and the match
+//! is opened.
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::diagnostic;
+use crate::parser::{utf16_len, ConfigFile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    /// Byte offsets of the offending part of the pattern.
+    pub span: (usize, usize),
+    pub message: String,
+}
+
+/// A `link` value split into its pattern and action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkValue<'a> {
+    pub pattern: &'a str,
+    pub action: Option<&'a str>,
+}
+
+impl<'a> LinkValue<'a> {
+    pub fn parse(value: &'a str) -> Self {
+        let split = value.rmatch_indices('=').find(|(idx, _)| {
+            let action = &value[idx + 1..];
+            let name = action.split(':').next().unwrap_or(action);
+            name.starts_with(|c: char| c.is_ascii_lowercase())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        });
+        match split {
+            Some((idx, _)) => Self {
+                pattern: &value[..idx],
+                action: Some(&value[idx + 1..]),
+            },
+            None => Self {
+                pattern: value,
+                action: None,
+            },
+        }
+    }
+
+    /// The byte offset of the action in the value.
+    pub fn action_offset(&self) -> usize {
+        self.pattern.len() + 1
+    }
+}
+
+/// Checks the action of a `link`: `open` or a keybinding action, which is
+/// run as if its keybinding was pressed. Returns the error message.
+pub fn validate_action(action: &str) -> Result<(), String> {
+    match action.split_once(':') {
+        Some(("open", _)) => Err("the `open` action takes no parameter".to_string()),
+        Some((name, "")) => Err(format!("the `{name}` action is missing its parameter")),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the syntax of `pattern`, returning the first error.
+pub fn validate(pattern: &str) -> Result<(), RegexError> {
+    let mut parser = Parser {
+        pattern,
+        chars: pattern.char_indices().collect(),
+        pos: 0,
+        groups: 0,
+        names: Vec::new(),
+    };
+    parser.parse_alternation(None)
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    groups: u32,
+    names: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|(_, c)| *c)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).map(|(_, c)| *c)
+    }
+
+    fn offset(&self, pos: usize) -> usize {
+        self.chars
+            .get(pos)
+            .map(|(idx, _)| *idx)
+            .unwrap_or(self.pattern.len())
+    }
+
+    fn error(&self, start: usize, end: usize, message: &str) -> RegexError {
+        RegexError {
+            span: (self.offset(start), self.offset(end)),
+            message: message.to_string(),
+        }
+    }
+
+    /// Parses alternatives up to the closing parenthesis of the group that
+    /// started at `group_start`, or to the end of the pattern.
+    fn parse_alternation(&mut self, group_start: Option<usize>) -> Result<(), RegexError> {
+        loop {
+            self.parse_sequence()?;
+            match self.peek() {
+                Some('|') => self.pos += 1,
+                Some(')') => {
+                    return match group_start {
+                        Some(_) => {
+                            self.pos += 1;
+                            Ok(())
+                        }
+                        None => {
+                            Err(self.error(self.pos, self.pos + 1, "unmatched close parenthesis"))
+                        }
+                    };
+                }
+                _ => {
+                    return match group_start {
+                        Some(start) => Err(self.error(
+                            start,
+                            self.chars.len(),
+                            "end pattern with unmatched parenthesis",
+                        )),
+                        None => Ok(()),
+                    };
+                }
+            }
+        }
+    }
+
+    fn parse_sequence(&mut self) -> Result<(), RegexError> {
+        // Whether the previous item can be repeated by a quantifier.
+        let mut repeatable = false;
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            match c {
+                '|' | ')' => return Ok(()),
+                '(' => {
+                    self.pos += 1;
+                    repeatable = self.parse_group(start)?;
+                }
+                '[' => {
+                    self.pos += 1;
+                    self.parse_class(start)?;
+                    repeatable = true;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    repeatable = self.parse_escape(start)?;
+                }
+                '*' | '+' | '?' => {
+                    if !repeatable {
+                        return Err(self.error(
+                            start,
+                            start + 1,
+                            "target of repeat operator is not specified",
+                        ));
+                    }
+                    self.pos += 1;
+                    self.skip_quantifier_suffix();
+                }
+                '{' => match self.parse_interval()? {
+                    Some(()) if !repeatable => {
+                        return Err(self.error(
+                            start,
+                            self.pos,
+                            "target of repeat operator is not specified",
+                        ));
+                    }
+                    Some(()) => self.skip_quantifier_suffix(),
+                    // Braces that are not an interval are literals.
+                    None => {
+                        self.pos += 1;
+                        repeatable = true;
+                    }
+                },
+                '^' | '$' => {
+                    self.pos += 1;
+                    repeatable = false;
+                }
+                _ => {
+                    self.pos += 1;
+                    repeatable = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_quantifier_suffix(&mut self) {
+        if matches!(self.peek(), Some('?' | '+')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses `{n}`, `{n,}`, `{,m}` or `{n,m}`. Returns `None` without
+    /// consuming anything when the brace does not start an interval.
+    fn parse_interval(&mut self) -> Result<Option<()>, RegexError> {
+        let start = self.pos;
+        let mut end = start + 1;
+        let mut text = String::new();
+        while let Some((_, c)) = self.chars.get(end) {
+            if *c == '}' {
+                break;
+            }
+            text.push(*c);
+            end += 1;
+        }
+        if self.chars.get(end).is_none() {
+            return Ok(None);
+        }
+        let (lower, upper) = match text.split_once(',') {
+            Some((lower, upper)) => (lower, Some(upper)),
+            None => (text.as_str(), None),
+        };
+        let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        let valid = match upper {
+            None => is_number(lower),
+            Some(upper) => {
+                (is_number(lower) || lower.is_empty())
+                    && (is_number(upper) || upper.is_empty())
+                    && !(lower.is_empty() && upper.is_empty())
+            }
+        };
+        if !valid {
+            return Ok(None);
+        }
+        self.pos = end + 1;
+        if let (Ok(lower), Some(Ok(upper))) = (lower.parse::<u64>(), upper.map(str::parse::<u64>)) {
+            if lower > upper {
+                return Err(self.error(
+                    start,
+                    self.pos,
+                    "upper is smaller than lower in repeat range",
+                ));
+            }
+        }
+        Ok(Some(()))
+    }
+
+    /// Parses a group after its `(`. Returns whether the group can be
+    /// repeated, which is not the case for option-only groups like `(?i)`.
+    fn parse_group(&mut self, start: usize) -> Result<bool, RegexError> {
+        if self.peek() != Some('?') {
+            self.groups += 1;
+            self.parse_alternation(Some(start))?;
+            return Ok(true);
+        }
+        self.pos += 1;
+        match self.peek() {
+            Some(':' | '=' | '!' | '>') => self.pos += 1,
+            Some('<') if matches!(self.peek_at(1), Some('=' | '!')) => self.pos += 2,
+            Some(open @ ('<' | '\'')) => {
+                let close = if open == '<' { '>' } else { '\'' };
+                self.pos += 1;
+                let name_start = self.pos;
+                let mut name = String::new();
+                while let Some(c) = self.peek() {
+                    if c == close {
+                        break;
+                    }
+                    name.push(c);
+                    self.pos += 1;
+                }
+                let valid = self.peek() == Some(close)
+                    && name
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(self.error(name_start, self.pos, "invalid group name <>"));
+                }
+                self.pos += 1;
+                self.groups += 1;
+                self.names.push(name);
+            }
+            Some('#') => {
+                while let Some(c) = self.peek() {
+                    self.pos += 1;
+                    if c == ')' {
+                        return Ok(false);
+                    }
+                }
+                return Err(self.error(start, self.chars.len(), "end pattern in group"));
+            }
+            _ => {
+                let options_start = self.pos;
+                while let Some(c) = self.peek() {
+                    match c {
+                        'i' | 'm' | 'x' | '-' => self.pos += 1,
+                        ')' => {
+                            self.pos += 1;
+                            return Ok(false);
+                        }
+                        ':' => break,
+                        _ => {
+                            return Err(self.error(
+                                options_start,
+                                self.pos + 1,
+                                "undefined group option",
+                            ));
+                        }
+                    }
+                }
+                if self.peek().is_none() {
+                    return Err(self.error(start, self.chars.len(), "end pattern in group"));
+                }
+                self.pos += 1;
+            }
+        }
+        self.parse_alternation(Some(start))?;
+        Ok(true)
+    }
+
+    /// Parses a character class after its `[`.
+    fn parse_class(&mut self, start: usize) -> Result<(), RegexError> {
+        if self.peek() == Some('^') {
+            self.pos += 1;
+        }
+        // A `]` right after the opening bracket is a literal.
+        let mut previous: Option<char> = None;
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            previous = Some(']');
+        }
+        loop {
+            let item_start = self.pos;
+            let Some(c) = self.peek() else {
+                return Err(self.error(start, self.chars.len(), "premature end of char-class"));
+            };
+            match c {
+                ']' => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                '[' if self.peek_at(1) == Some(':') => {
+                    self.parse_posix_bracket()?;
+                    previous = None;
+                }
+                '[' => {
+                    self.pos += 1;
+                    self.parse_class(item_start)?;
+                    previous = None;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    previous = self.parse_class_escape(item_start)?;
+                }
+                '-' if previous.is_some()
+                    && self
+                        .peek_at(1)
+                        .is_some_and(|next| next != ']' && next != '[') =>
+                {
+                    self.pos += 1;
+                    let end = if self.peek() == Some('\\') {
+                        self.pos += 1;
+                        self.parse_class_escape(self.pos - 1)?
+                    } else {
+                        self.pos += 1;
+                        self.chars.get(self.pos - 1).map(|(_, c)| *c)
+                    };
+                    if let (Some(low), Some(high)) = (previous, end) {
+                        if low > high {
+                            return Err(self.error(
+                                item_start - 1,
+                                self.pos,
+                                "empty range in char class",
+                            ));
+                        }
+                    }
+                    previous = None;
+                }
+                _ => {
+                    self.pos += 1;
+                    previous = Some(c);
+                }
+            }
+        }
+    }
+
+    fn parse_posix_bracket(&mut self) -> Result<(), RegexError> {
+        const NAMES: [&str; 14] = [
+            "alnum", "alpha", "ascii", "blank", "cntrl", "digit", "graph", "lower", "print",
+            "punct", "space", "upper", "xdigit", "word",
+        ];
+        let start = self.pos;
+        self.pos += 2;
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c == ':' && self.peek_at(1) == Some(']') {
+                self.pos += 2;
+                let name = name.strip_prefix('^').unwrap_or(&name);
+                if NAMES.contains(&name) {
+                    return Ok(());
+                }
+                return Err(self.error(start, self.pos, "invalid POSIX bracket type"));
+            }
+            name.push(c);
+            self.pos += 1;
+        }
+        Err(self.error(start, self.chars.len(), "premature end of char-class"))
+    }
+
+    /// Parses an escape inside a character class after its `\`, returning
+    /// the literal character it stands for when there is one.
+    fn parse_class_escape(&mut self, start: usize) -> Result<Option<char>, RegexError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error(start, self.chars.len(), "end pattern at escape"));
+        };
+        match c {
+            'x' | 'u' | 'p' | 'P' => {
+                self.parse_escape(start)?;
+                Ok(None)
+            }
+            'n' => {
+                self.pos += 1;
+                Ok(Some('\n'))
+            }
+            't' => {
+                self.pos += 1;
+                Ok(Some('\t'))
+            }
+            c if c.is_ascii_alphanumeric() => {
+                self.pos += 1;
+                Ok(None)
+            }
+            c => {
+                self.pos += 1;
+                Ok(Some(c))
+            }
+        }
+    }
+
+    /// Parses an escape after its `\`. Returns whether it can be repeated.
+    fn parse_escape(&mut self, start: usize) -> Result<bool, RegexError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error(start, self.chars.len(), "end pattern at escape"));
+        };
+        self.pos += 1;
+        match c {
+            'x' if self.peek() == Some('{') => {
+                self.pos += 1;
+                self.expect_hex_until('}', start, 1, 8)?;
+            }
+            'x' => {
+                let mut digits = 0;
+                while digits < 2 && self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    self.pos += 1;
+                    digits += 1;
+                }
+            }
+            'u' => {
+                for _ in 0..4 {
+                    if !self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                        return Err(self.error(start, self.pos, "invalid code point value"));
+                    }
+                    self.pos += 1;
+                }
+            }
+            'p' | 'P' => {
+                if self.peek() != Some('{') {
+                    return Err(self.error(start, self.pos, "invalid character property name {}"));
+                }
+                while let Some(c) = self.peek() {
+                    self.pos += 1;
+                    if c == '}' {
+                        return Ok(true);
+                    }
+                }
+                return Err(self.error(start, self.pos, "invalid character property name {}"));
+            }
+            'k' => {
+                if self.peek() != Some('<') {
+                    return Err(self.error(start, self.pos, "invalid backref number/name"));
+                }
+                self.pos += 1;
+                let mut name = String::new();
+                while let Some(c) = self.peek() {
+                    self.pos += 1;
+                    if c == '>' {
+                        let known = match name.parse::<u32>() {
+                            Ok(number) => number >= 1 && number <= self.groups,
+                            Err(_) => self.names.contains(&name),
+                        };
+                        if !known {
+                            return Err(self.error(start, self.pos, "undefined name <> reference"));
+                        }
+                        return Ok(true);
+                    }
+                    name.push(c);
+                }
+                return Err(self.error(start, self.pos, "invalid backref number/name"));
+            }
+            '1'..='9' => {
+                let mut number = c.to_digit(10).unwrap_or(0);
+                while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+                    number = number.saturating_mul(10).saturating_add(digit);
+                    self.pos += 1;
+                }
+                if number > self.groups {
+                    return Err(self.error(start, self.pos, "invalid backref number/name"));
+                }
+            }
+            // Anchors and zero-width assertions.
+            'A' | 'z' | 'Z' | 'b' | 'B' | 'G' => return Ok(false),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn expect_hex_until(
+        &mut self,
+        close: char,
+        start: usize,
+        min: usize,
+        max: usize,
+    ) -> Result<(), RegexError> {
+        let mut digits = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == close {
+                if digits < min {
+                    break;
+                }
+                return Ok(());
+            }
+            if !c.is_ascii_hexdigit() || digits == max {
+                break;
+            }
+            digits += 1;
+        }
+        Err(self.error(start, self.pos, "invalid code point value"))
+    }
+}
+
+pub fn link_diagnostics(config: &ConfigFile) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for entry in config.entries() {
+        if entry.key != "link" || entry.value.is_empty() {
+            continue;
+        }
+        let link = LinkValue::parse(&entry.value);
+        if let Err(error) = validate(link.pattern) {
+            diagnostics.push(diagnostic(
+                entry.value_subrange(error.span.0, error.span.1.max(error.span.0)),
+                DiagnosticSeverity::ERROR,
+                "invalid-link-regex",
+                format!("invalid regular expression: {}", error.message),
+            ));
+        }
+        if let Some(Err(message)) = link.action.map(validate_action) {
+            diagnostics.push(diagnostic(
+                entry.value_subrange(link.action_offset(), entry.value.len()),
+                DiagnosticSeverity::ERROR,
+                "invalid-link-action",
+                message,
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// The `ghostty/testLink` request runs a `link` pattern against sample text.
+/// The `link` value is either given directly as `pattern` or taken from the
+/// `link` entry at `position` in `text_document`. Only its pattern is run,
+/// and its action is returned along with the matches.
+pub enum TestLink {}
+
+impl lsp_types::request::Request for TestLink {
+    type Params = TestLinkParams;
+    type Result = TestLinkResult;
+    const METHOD: &'static str = "ghostty/testLink";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestLinkParams {
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub text_document: Option<TextDocumentIdentifier>,
+    #[serde(default)]
+    pub position: Option<Position>,
+    /// The sample text to run the pattern against.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestLinkResult {
+    pub matches: Vec<LinkMatch>,
+    /// The action clicking a match takes, when the value has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkMatch {
+    /// UTF-16 offsets of the match within the sample text.
+    pub start: u32,
+    pub end: u32,
+    pub text: String,
+}
+
+/// The most matches a preview returns.
+const MAX_MATCHES: usize = 1000;
+
+/// Runs the pattern of the `link` value `value` against `text`.
+pub fn test_link(value: &str, text: &str) -> TestLinkResult {
+    let link = LinkValue::parse(value);
+    let mut result = TestLinkResult {
+        action: link.action.map(str::to_string),
+        ..Default::default()
+    };
+    if let Some(Err(message)) = link.action.map(validate_action) {
+        result.error = Some(message);
+        return result;
+    }
+    if let Err(error) = validate(link.pattern) {
+        result.error = Some(error.message);
+        return result;
+    }
+    let regex = match fancy_regex::Regex::new(link.pattern) {
+        Ok(regex) => regex,
+        Err(error) => {
+            result.error = Some(format!(
+                "the preview does not support this pattern: {error}"
+            ));
+            return result;
+        }
+    };
+    for found in regex.find_iter(text).take(MAX_MATCHES) {
+        match found {
+            Ok(found) => result.matches.push(LinkMatch {
+                start: utf16_len(&text[..found.start()]),
+                end: utf16_len(&text[..found.end()]),
+                text: found.as_str().to_string(),
+            }),
+            Err(error) => {
+                result.error = Some(error.to_string());
+                break;
+            }
+        }
+    }
+    result
+}
+
+/// Resolves the `link` value a `ghostty/testLink` request refers to.
+pub fn requested_pattern(params: &TestLinkParams, config: Option<&ConfigFile>) -> Option<String> {
+    if let Some(pattern) = &params.pattern {
+        return Some(pattern.clone());
+    }
+    let entry = config?.entry_at_line(params.position?.line)?;
+    (entry.key == "link").then(|| entry.value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(pattern: &str) -> Option<(usize, usize, String)> {
+        validate(pattern)
+            .err()
+            .map(|error| (error.span.0, error.span.1, error.message))
+    }
+
+    #[test]
+    fn test_valid_patterns() {
+        for pattern in [
+            r"https?://[\w.-]+(?:/\S*)?",
+            r"(?<path>[~/][^\s:]+):(\d+)\k<path>\1",
+            r"(?i)\bfoo{2,3}+(?=bar)[[:alpha:]a-z\]]",
+            r"a{,3}b{x}\x{1F600}\p{Greek}",
+        ] {
+            assert_eq!(error(pattern), None, "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert_eq!(
+            error("(ab"),
+            Some((0, 3, "end pattern with unmatched parenthesis".to_string()))
+        );
+        assert_eq!(
+            error("ab)"),
+            Some((2, 3, "unmatched close parenthesis".to_string()))
+        );
+        assert_eq!(
+            error("a|*b"),
+            Some((
+                2,
+                3,
+                "target of repeat operator is not specified".to_string()
+            ))
+        );
+        assert_eq!(
+            error("[a-"),
+            Some((0, 3, "premature end of char-class".to_string()))
+        );
+        assert_eq!(
+            error("x[z-a]"),
+            Some((2, 5, "empty range in char class".to_string()))
+        );
+        assert_eq!(
+            error("a{3,1}"),
+            Some((
+                1,
+                6,
+                "upper is smaller than lower in repeat range".to_string()
+            ))
+        );
+        assert_eq!(
+            error(r"(a)\2"),
+            Some((3, 5, "invalid backref number/name".to_string()))
+        );
+        assert_eq!(
+            error(r"ab\"),
+            Some((2, 3, "end pattern at escape".to_string()))
+        );
+        assert_eq!(
+            error("(?q)"),
+            Some((2, 3, "undefined group option".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_link_value() {
+        assert_eq!(
+            LinkValue::parse(r"[?&]id=\d+"),
+            LinkValue {
+                pattern: r"[?&]id=\d+",
+                action: None
+            }
+        );
+        let link = LinkValue::parse("(?=x)a=b=open");
+        assert_eq!(link.pattern, "(?=x)a=b");
+        assert_eq!(link.action, Some("open"));
+        assert_eq!(link.action_offset(), 9);
+        assert_eq!(
+            LinkValue::parse("JIRA-[0-9]+=write_screen_file:open").action,
+            Some("write_screen_file:open")
+        );
+    }
+
+    #[test]
+    fn test_validate_action() {
+        assert_eq!(validate_action("open"), Ok(()));
+        assert_eq!(validate_action("csi:A"), Ok(()));
+        assert_eq!(
+            validate_action("open:now"),
+            Err("the `open` action takes no parameter".to_string())
+        );
+        assert_eq!(
+            validate_action("esc:"),
+            Err("the `esc` action is missing its parameter".to_string())
+        );
+    }
+
+    #[test]
+    fn test_link_diagnostics_with_action() {
+        let config = ConfigFile::parse("link = [0-9]+=open\nlink = (a=open:x\n");
+        let diagnostics = link_diagnostics(&config);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.range.start.line,
+                    d.range.start.character,
+                    d.range.end.character,
+                )
+            })
+            .collect();
+        assert_eq!(found, vec![(1, 7, 9), (1, 10, 16)]);
+    }
+
+    #[test]
+    fn test_link_diagnostic_range() {
+        let config = ConfigFile::parse("link = foo(bar");
+        let diagnostics = link_diagnostics(&config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.character, 10);
+        assert_eq!(diagnostics[0].range.end.character, 14);
+    }
+
+    #[test]
+    fn test_link_matches() {
+        let result = test_link(r"https?://\S+", "see https://a.b/c and http://d.e");
+        assert_eq!(result.error, None);
+        let matches: Vec<_> = result
+            .matches
+            .iter()
+            .map(|m| (m.start, m.end, m.text.as_str()))
+            .collect();
+        assert_eq!(
+            matches,
+            vec![(4, 17, "https://a.b/c"), (22, 32, "http://d.e")]
+        );
+        assert!(test_link("(", "text").error.is_some());
+    }
+
+    #[test]
+    fn test_link_matches_with_action() {
+        let result = test_link("#[0-9]+=open", "fixes #12 and #345");
+        assert_eq!(result.error, None);
+        assert_eq!(result.action.as_deref(), Some("open"));
+        let matches: Vec<_> = result.matches.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(matches, vec!["#12", "#345"]);
+        assert_eq!(
+            test_link("#[0-9]+=open:now", "#1").error.as_deref(),
+            Some("the `open` action takes no parameter")
+        );
+    }
+
+    #[test]
+    fn test_requested_pattern_from_document() {
+        let config = ConfigFile::parse("font-size = 12\nlink = [0-9]+\n");
+        let params = TestLinkParams {
+            pattern: None,
+            text_document: None,
+            position: Some(Position::new(1, 3)),
+            text: String::new(),
+        };
+        assert_eq!(
+            requested_pattern(&params, Some(&config)).as_deref(),
+            Some("[0-9]+")
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Document links for paths in values and URLs in comments.
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, DocumentLink, Position, Range, Url};
+
+use crate::diagnostics::diagnostic;
+use crate::includes::resolve_config_file;
+use crate::parser::{utf16_len, ConfigFile, Entry, LineKind};
+use crate::paths;
+use crate::working_directory;
+
+/// A path written in the value of an entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathValue<'a> {
+    pub entry: &'a Entry,
+    pub range: Range,
+    pub path: PathBuf,
+    /// Optional paths, such as `config-file = ?extra`, may be missing.
+    pub optional: bool,
+}
+
+/// Returns every path referenced by `config-file`, `working-directory`,
+/// `custom-shader` and `command`. Relative paths are resolved against
+/// `base_dir` and skipped when there is none.
+pub fn path_values<'a>(base_dir: Option<&Path>, config: &'a ConfigFile) -> Vec<PathValue<'a>> {
+    let mut values = Vec::new();
+    for entry in config.entries() {
+        let value = entry.value.as_str();
+        let (start, written, optional) = match entry.key.as_str() {
+            "config-file" => match value.strip_prefix('?') {
+                Some(rest) => (1, rest, true),
+                None => (0, value, false),
+            },
+            "custom-shader" => (0, value, false),
+            "working-directory" if value != "home" && value != "inherit" => (0, value, false),
+            "command" => {
+                let program = value.split_whitespace().next().unwrap_or("");
+                if !program.contains('/') {
+                    continue;
+                }
+                (0, program, false)
+            }
+            _ => continue,
+        };
+        if written.is_empty() {
+            continue;
+        }
+        let path = if entry.key == "working-directory" {
+            match working_directory::resolve(base_dir, written) {
+                Some(path) => path,
+                None => continue,
+            }
+        } else {
+            let expanded = paths::expand_home(written);
+            match (expanded.is_absolute(), base_dir) {
+                (true, _) => expanded,
+                (false, Some(base_dir)) if entry.key == "config-file" => {
+                    resolve_config_file(base_dir, written).0
+                }
+                (false, Some(base_dir)) => paths::resolve(base_dir, written),
+                (false, None) => continue,
+            }
+        };
+        values.push(PathValue {
+            entry,
+            range: entry.value_subrange(start, start + written.len()),
+            path,
+            optional,
+        });
+    }
+    values
+}
+
+pub fn document_links(uri: &Url, config: &ConfigFile) -> Vec<DocumentLink> {
+    let base_dir = base_dir(uri);
+    let mut links: Vec<DocumentLink> = path_values(base_dir.as_deref(), config)
+        .into_iter()
+        .filter_map(|value| {
+            let target = Url::from_file_path(&value.path).ok()?;
+            Some(link(value.range, target))
+        })
+        .collect();
+
+    for line in &config.lines {
+        if line.kind != LineKind::Comment {
+            continue;
+        }
+        for (start, url) in find_urls(&line.text) {
+            if let Ok(target) = Url::parse(url) {
+                let range = Range::new(
+                    Position::new(line.number, utf16_len(&line.text[..start])),
+                    Position::new(line.number, utf16_len(&line.text[..start + url.len()])),
+                );
+                links.push(link(range, target));
+            }
+        }
+    }
+    links
+}
+
+/// Warns about paths that do not exist on disk. `working-directory` is
+/// checked separately by [`working_directory::working_directory_diagnostics`].
+pub fn missing_path_diagnostics(uri: &Url, config: &ConfigFile) -> Vec<Diagnostic> {
+    let base_dir = base_dir(uri);
+    path_values(base_dir.as_deref(), config)
+        .into_iter()
+        .filter(|value| value.entry.key != "working-directory")
+        .filter(|value| !value.optional && !value.path.exists())
+        .map(|value| {
+            diagnostic(
+                value.range,
+                DiagnosticSeverity::WARNING,
+                "missing-path",
+                format!("`{}` does not exist", value.path.display()),
+            )
+        })
+        .collect()
+}
+
+/// The directory relative paths in the document are resolved against.
+pub fn base_dir(uri: &Url) -> Option<PathBuf> {
+    let path = uri.to_file_path().ok()?;
+    path.parent().map(Path::to_path_buf)
+}
+
+fn link(range: Range, target: Url) -> DocumentLink {
+    DocumentLink {
+        range,
+        target: Some(target),
+        tooltip: None,
+        data: None,
+    }
+}
+
+/// Finds `http://` and `https://` URLs in `text`, returning the byte offset
+/// of each match.
+fn find_urls(text: &str) -> Vec<(usize, &str)> {
+    let mut urls = Vec::new();
+    let mut offset = 0;
+    while let Some(idx) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| text[offset..].find(scheme))
+        .min()
+    {
+        let start = offset + idx;
+        let rest = &text[start..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '<' || c == '>')
+            .unwrap_or(rest.len());
+        let url = rest[..len].trim_end_matches(['.', ',', ')', ';', ':']);
+        urls.push((start, url));
+        offset = start + len.max(1);
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_links() {
+        let uri = Url::parse("file:///etc/ghostty/config").unwrap();
+        let config = ConfigFile::parse(
+            "# see https://ghostty.org/docs.\nconfig-file = ?extra\ncustom-shader = /tmp/a.glsl\nworking-directory = home\ncommand = ./bin/fish --login\ncommand = fish\n",
+        );
+        let links: Vec<_> = document_links(&uri, &config)
+            .into_iter()
+            .map(|link| (link.range, link.target.unwrap().to_string()))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                (
+                    Range::new(Position::new(1, 15), Position::new(1, 20)),
+                    "file:///etc/ghostty/extra".to_string()
+                ),
+                (
+                    Range::new(Position::new(2, 16), Position::new(2, 27)),
+                    "file:///tmp/a.glsl".to_string()
+                ),
+                (
+                    Range::new(Position::new(4, 10), Position::new(4, 20)),
+                    "file:///etc/ghostty/bin/fish".to_string()
+                ),
+                (
+                    Range::new(Position::new(0, 6), Position::new(0, 30)),
+                    "https://ghostty.org/docs".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_path_diagnostics() {
+        let uri = Url::parse("file:///nonexistent/config").unwrap();
+        let config = ConfigFile::parse("config-file = ?optional\nconfig-file = required\n");
+        let diagnostics = missing_path_diagnostics(&uri, &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`/nonexistent/required` does not exist"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Logging for the language server.
+//!
+//! Records go through the `log` facade and are written to stderr or a log
+//! file when they pass a `RUST_LOG`-style [`Filter`]. Warnings and errors are
+//! also forwarded to every connected client as `window/logMessage`, and the
+//! messages a client sends are reported back to it as `$/logTrace` when it
+//! turned tracing on. Document contents are left out of both unless asked
+//! for.
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crossbeam_channel::Sender;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use lsp_server::{Message, Notification};
+use lsp_types::notification::{LogMessage, LogTrace, Notification as _};
+use lsp_types::{LogMessageParams, LogTraceParams, MessageType, TraceValue};
+use serde_json::Value;
+
+/// The filter used when neither `--log-level` nor `RUST_LOG` is set.
+pub const DEFAULT_FILTER: &str = "info";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogOptions {
+    /// A `RUST_LOG`-style filter, overriding the `RUST_LOG` variable.
+    pub filter: Option<Filter>,
+    /// Appends to this file instead of writing to stderr.
+    pub file: Option<PathBuf>,
+    /// Logs the text of documents instead of redacting it.
+    pub contents: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    /// The module path the level applies to, or `None` for every module.
+    target: Option<String>,
+    level: LevelFilter,
+}
+
+/// Log levels per module, written as comma separated `[target=]level`
+/// directives such as `warn,ghostty_lsp::fonts=debug`. The directive with
+/// the longest matching target decides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    directives: Vec<Directive>,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_level = |level: &str| {
+            level
+                .parse::<LevelFilter>()
+                .map_err(|_| format!("unknown log level `{level}`"))
+        };
+        let mut directives = Vec::new();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            directives.push(match directive.split_once('=') {
+                Some((target, level)) => Directive {
+                    target: Some(target.trim().to_string()),
+                    level: parse_level(level.trim())?,
+                },
+                // A bare word is a level if it is one and a target that
+                // logs everything otherwise, like `RUST_LOG`.
+                None => match parse_level(directive) {
+                    Ok(level) => Directive {
+                        target: None,
+                        level,
+                    },
+                    Err(_) => Directive {
+                        target: Some(directive.to_string()),
+                        level: LevelFilter::Trace,
+                    },
+                },
+            });
+        }
+        Ok(Self { directives })
+    }
+}
+
+impl Filter {
+    /// The level enabled for `target`. Without a matching directive only
+    /// errors are logged.
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|directive| match &directive.target {
+                None => true,
+                Some(prefix) => {
+                    target == prefix
+                        || target
+                            .strip_prefix(prefix.as_str())
+                            .is_some_and(|rest| rest.starts_with("::"))
+                }
+            })
+            .max_by_key(|directive| directive.target.as_ref().map_or(0, String::len))
+            .map_or(LevelFilter::Error, |directive| directive.level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|directive| directive.level)
+            .max()
+            .unwrap_or(LevelFilter::Error)
+    }
+}
+
+struct Logger {
+    filter: Filter,
+    output: Mutex<Box<dyn Write + Send>>,
+    clients: Mutex<Vec<Sender<Message>>>,
+    contents: AtomicBool,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // `lsp_server` logs every message verbatim at debug level, so its
+        // debug output is only let through when contents may be logged.
+        if metadata.target().starts_with("lsp_server")
+            && metadata.level() > Level::Info
+            && !self.contents.load(Ordering::Relaxed)
+        {
+            return false;
+        }
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut output) = self.output.lock() {
+            let _ = writeln!(
+                output,
+                "{:<5} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+        let typ = match record.level() {
+            Level::Error => MessageType::ERROR,
+            Level::Warn => MessageType::WARNING,
+            _ => return,
+        };
+        let params = LogMessageParams {
+            typ,
+            message: record.args().to_string(),
+        };
+        send(Notification::new(LogMessage::METHOD.to_string(), params));
+    }
+
+    fn flush(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            let _ = output.flush();
+        }
+    }
+}
+
+/// Sends a notification to every attached client.
+fn send(notif: Notification) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    if let Ok(clients) = logger.clients.lock() {
+        for sender in clients.iter() {
+            let _ = sender.send(Message::Notification(notif.clone()));
+        }
+    }
+}
+
+/// Installs the logger. The filter comes from the options, then `RUST_LOG`,
+/// then [`DEFAULT_FILTER`].
+pub fn init(options: &LogOptions) -> Result<(), String> {
+    let filter = match &options.filter {
+        Some(filter) => filter.clone(),
+        None => std::env::var("RUST_LOG")
+            .ok()
+            .filter(|filter| !filter.trim().is_empty())
+            .map_or_else(|| DEFAULT_FILTER.parse(), |filter| filter.parse())
+            .map_err(|err| format!("RUST_LOG: {err}"))?,
+    };
+    let output: Box<dyn Write + Send> = match &options.file {
+        Some(path) => Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| format!("cannot open `{}`: {err}", path.display()))?,
+        ),
+        None => Box::new(io::stderr()),
+    };
+    let max_level = filter.max_level();
+    let logger = LOGGER.get_or_init(|| Logger {
+        filter,
+        output: Mutex::new(output),
+        clients: Mutex::new(Vec::new()),
+        contents: AtomicBool::new(false),
+    });
+    logger.contents.store(options.contents, Ordering::Relaxed);
+    log::set_logger(logger).map_err(|err| err.to_string())?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// Forwards warnings and errors to a client over `sender`, along with any
+/// other attached client.
+pub fn attach_client(sender: Sender<Message>) {
+    if let Some(logger) = LOGGER.get() {
+        if let Ok(mut clients) = logger.clients.lock() {
+            clients.push(sender);
+        }
+    }
+}
+
+/// Stops forwarding to the client attached with `sender`. This has to
+/// happen before its connection shuts down, as the sender keeps the
+/// connection's writer alive.
+pub fn detach_client(sender: &Sender<Message>) {
+    if let Some(logger) = LOGGER.get() {
+        if let Ok(mut clients) = logger.clients.lock() {
+            clients.retain(|client| !client.same_channel(sender));
+        }
+    }
+}
+
+/// Reports `message` to the client over `sender` as `$/logTrace` if `trace`
+/// is on, with the details from `verbose` if it is verbose.
+pub fn log_trace(
+    sender: &Sender<Message>,
+    trace: TraceValue,
+    message: impl FnOnce() -> String,
+    verbose: impl FnOnce() -> String,
+) {
+    if trace == TraceValue::Off {
+        return;
+    }
+    let params = LogTraceParams {
+        message: message(),
+        verbose: (trace == TraceValue::Verbose).then(verbose),
+    };
+    let notif = Notification::new(LogTrace::METHOD.to_string(), params);
+    let _ = sender.send(Message::Notification(notif));
+}
+
+/// The fields holding document text: `text` in `didOpen` and `didChange`,
+/// and `newText` in edits.
+const CONTENT_FIELDS: [&str; 2] = ["text", "newText"];
+
+/// `value` with the text of documents replaced by its length.
+pub fn redact_contents(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(text) if CONTENT_FIELDS.contains(&key.as_str()) => {
+                            Value::String(format!("<{} bytes redacted>", text.len()))
+                        }
+                        value => redact_contents(value),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(redact_contents).collect()),
+        value => value.clone(),
+    }
+}
+
+/// `value` as it may be logged: redacted unless the logger was set up to
+/// log document contents.
+pub fn loggable(value: &Value) -> Value {
+    let contents = LOGGER
+        .get()
+        .is_some_and(|logger| logger.contents.load(Ordering::Relaxed));
+    if contents {
+        value.clone()
+    } else {
+        redact_contents(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter: Filter = "warn,ghostty_lsp=info,ghostty_lsp::fonts=trace"
+            .parse()
+            .unwrap();
+        assert_eq!(filter.level("lsp_server"), LevelFilter::Warn);
+        assert_eq!(filter.level("ghostty_lsp"), LevelFilter::Info);
+        assert_eq!(filter.level("ghostty_lsp::handlers"), LevelFilter::Info);
+        assert_eq!(filter.level("ghostty_lsp::fonts"), LevelFilter::Trace);
+        assert_eq!(filter.level("ghostty_lsp_extra"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        let filter: Filter = "ghostty_lsp".parse().unwrap();
+        assert_eq!(filter.level("ghostty_lsp::fonts"), LevelFilter::Trace);
+        assert_eq!(filter.level("lsp_server"), LevelFilter::Error);
+        assert_eq!(
+            "OFF".parse::<Filter>().unwrap().level("x"),
+            LevelFilter::Off
+        );
+        assert!("ghostty_lsp=loud".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_redact_contents() {
+        let params = serde_json::json!({
+            "textDocument": { "uri": "file:///config", "text": "command = secret" },
+            "contentChanges": [{ "text": "abc" }],
+            "edits": [{ "newText": "x", "range": null }],
+        });
+        assert_eq!(
+            redact_contents(&params),
+            serde_json::json!({
+                "textDocument": { "uri": "file:///config", "text": "<16 bytes redacted>" },
+                "contentChanges": [{ "text": "<3 bytes redacted>" }],
+                "edits": [{ "newText": "<1 bytes redacted>", "range": null }],
+            })
+        );
+    }
+}
@@ -1,116 +1,354 @@
-//! A minimal example LSP server that can only respond to the `gotoDefinition` request. To use
-//! this example, execute it and then send an `initialize` request.
+//! `ghostty-lsp`, a language server for Ghostty configuration files.
 //!
-//! ```no_run
-//! Content-Length: 85
+//! Without a command it serves one client over stdio or a TCP address
+//! (`--listen`, `--connect`), or every client that connects to a Unix domain
+//! socket (`--socket`), each on a thread of its own. The main loop of a
+//! client applies notifications to its state and answers requests on worker
+//! threads, publishing or refreshing diagnostics as documents change.
 //!
-//! {"jsonrpc": "2.0", "method": "initialize", "id": 1, "params": {"capabilities": {}}}
-//! ```
-//!
-//! This will respond with a server response. Then send it a `initialized` notification which will
-//! have no response.
-//!
-//! ```no_run
-//! Content-Length: 59
-//!
-//! {"jsonrpc": "2.0", "method": "initialized", "params": {}}
-//! ```
-//!
-//! Once these two are sent, then we enter the main loop of the server. The only request this
-//! example can handle is `gotoDefinition`:
-//!
-//! ```no_run
-//! Content-Length: 159
-//!
-//! {"jsonrpc": "2.0", "method": "textDocument/definition", "id": 2, "params": {"textDocument": {"uri": "file://temp"}, "position": {"line": 1, "character": 1}}}
-//! ```
-//!
-//! To finish up without errors, send a shutdown request:
-//!
-//! ```no_run
-//! Content-Length: 67
-//!
-//! {"jsonrpc": "2.0", "method": "shutdown", "id": 3, "params": null}
-//! ```
-//!
-//! The server will exit the main loop and finally we send a `shutdown` notification to stop
-//! the server.
-//!
-//! ```
-//! Content-Length: 54
-//!
-//! {"jsonrpc": "2.0", "method": "exit", "params": null}
-//! ```
+//! The subcommands run the same checks without an editor: `check`, `fmt`,
+//! `explain`, `docs`, `dump` and `migrate`. `ghostty-lsp --help` lists
+//! their options.
 use std::error::Error;
 
-use lsp_server::{Connection, Message};
-use lsp_types::OneOf;
+use crossbeam_channel::{after, never, select, unbounded};
+use lsp_server::{Connection, ErrorCode, Message, Request, RequestId, Response};
+use lsp_types::notification::{Cancel, DidChangeConfiguration, Notification as _};
+use lsp_types::request::{RegisterCapability, WorkspaceConfiguration, WorkspaceDiagnosticRefresh};
 use lsp_types::{
-    HoverProviderCapability, InitializeParams, ServerCapabilities, TextDocumentSyncCapability,
-    TextDocumentSyncKind,
+    CancelParams, InitializeParams, InitializeResult, NumberOrString, ServerInfo, Url,
 };
 
-use ghostty_lsp::handlers::{handle_notification, handle_request};
+use ghostty_lsp::capabilities::{registrations, server_capabilities, ClientSupport};
+use ghostty_lsp::cli::{
+    self, check, docs, dump, explain, fmt, migrate_files, Command, ServeOptions, USAGE,
+};
+use ghostty_lsp::fonts::{font_dirs, FontCaches, FontIndex};
+use ghostty_lsp::handlers::{
+    handle_background, handle_notification, handle_request, publish_diagnostics, Refresh,
+};
+use ghostty_lsp::logging;
+use ghostty_lsp::settings::{configuration_params, Settings};
+use ghostty_lsp::state::ServerState;
+use ghostty_lsp::transport::{self, IoThreads, SocketListener, Transport};
+use ghostty_lsp::worker::{Debouncer, Generations, InFlight, WorkerPool, DIAGNOSTICS_DELAY};
+use ghostty_lsp::workspace;
 
 fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
-    // Note that  we must have our logging only write out to stderr.
-    eprintln!("Starting server");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(Command::Serve(options)) => serve(&options),
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Ok(Command::Check(options)) => {
+            let fonts = FontIndex::scan(&font_dirs());
+            let code = check(&options, fonts, &mut std::io::stdout().lock())?;
+            std::process::exit(code)
+        }
+        Ok(Command::Fmt(options)) => {
+            let code = fmt(&options, &mut std::io::stdout().lock())?;
+            std::process::exit(code)
+        }
+        Ok(Command::Explain(key)) => {
+            let code = explain(&key, &mut std::io::stdout().lock())?;
+            std::process::exit(code)
+        }
+        Ok(Command::Docs(format)) => {
+            docs(format, &mut std::io::stdout().lock())?;
+            Ok(())
+        }
+        Ok(Command::Dump(options)) => {
+            let code = dump(&options, &mut std::io::stdout().lock())?;
+            std::process::exit(code)
+        }
+        Ok(Command::Migrate(options)) => {
+            let code = migrate_files(&options, &mut std::io::stdout().lock())?;
+            std::process::exit(code)
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            std::process::exit(2)
+        }
+    }
+}
 
-    // Create the transport. Includes the stdio (stdin and stdout) versions but this could
-    // also be implemented to use sockets or HTTP.
-    let (connection, io_threads) = Connection::stdio();
+fn serve(options: &ServeOptions) -> Result<(), Box<dyn Error + Sync + Send>> {
+    // Stdout may be the transport, so the log goes to stderr or a file.
+    logging::init(&options.log)?;
+    log::info!("Starting server");
 
-    // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
-    let server_capabilities = serde_json::to_value(ServerCapabilities {
-        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
-        definition_provider: Some(OneOf::Left(true)),
-        completion_provider: Some(lsp_types::CompletionOptions {
-            resolve_provider: Some(true),
-            trigger_characters: None,
-            work_done_progress_options: Default::default(),
-            all_commit_characters: None,
-            completion_item: None,
-        }),
-        hover_provider: Some(HoverProviderCapability::Simple(true)),
-        ..Default::default()
-    })
-    .unwrap();
-    eprintln!("Sending server capabilities: {server_capabilities:?}");
-    let initialization_params = connection.initialize(server_capabilities)?;
-    main_loop(connection, initialization_params)?;
-    io_threads.join()?;
+    // Clients with the same font directories share one scan of them.
+    let font_caches = FontCaches::default();
+    if let Transport::Socket(path) = &options.transport {
+        // Editors that connect to the socket each get a thread of their own
+        // until the server is stopped.
+        let listener = SocketListener::bind(path)?;
+        loop {
+            let (connection, io_threads) = listener.accept()?;
+            let font_caches = font_caches.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = serve_client(connection, io_threads, font_caches) {
+                    log::error!("Serving a client failed: {err}");
+                }
+            });
+        }
+    }
+
+    // Create the transport. This blocks until a client is connected.
+    let (connection, io_threads) = transport::open(&options.transport)?;
+    serve_client(connection, io_threads, font_caches)?;
 
     // Shut down gracefully.
-    eprintln!("Shutting down server");
+    log::info!("Shutting down server");
+    Ok(())
+}
+
+fn serve_client(
+    connection: Connection,
+    io_threads: IoThreads,
+    font_caches: FontCaches,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    // The capabilities depend on what the client supports, so they are only
+    // sent once its `initialize` request is in.
+    let (id, params) = connection.initialize_start()?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let client = ClientSupport::new(&params.capabilities);
+    log::debug!("Client supports {client:?}");
+    let initialize_result = serde_json::to_value(InitializeResult {
+        capabilities: server_capabilities(&client),
+        server_info: Some(ServerInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    })?;
+    log::debug!("Sending initialize result: {initialize_result}");
+    connection.initialize_finish(id, initialize_result)?;
+
+    // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
+    let sender = connection.sender.clone();
+    logging::attach_client(sender.clone());
+    let result = main_loop(connection, params, client, font_caches);
+    // The client's sender keeps the transport open until it is dropped.
+    logging::detach_client(&sender);
+    drop(sender);
+    result?;
+    io_threads.join()?;
     Ok(())
 }
 
 fn main_loop(
     connection: Connection,
-    params: serde_json::Value,
+    params: InitializeParams,
+    client: ClientSupport,
+    font_caches: FontCaches,
 ) -> Result<(), Box<dyn Error + Sync + Send>> {
-    let _params: InitializeParams = serde_json::from_value(params).unwrap();
-    let mut doc: String = String::new();
-    eprintln!("Starting main loop");
-    for msg in &connection.receiver {
-        eprintln!("Got msg");
-        match msg {
-            Message::Request(req) => {
-                if connection.handle_shutdown(&req)? {
-                    return Ok(());
+    let roots = workspace::roots(&params);
+    log::debug!("Workspace roots: {roots:?}");
+    let settings = Settings::from_value(params.initialization_options.unwrap_or_default());
+    let (background_sender, background) = unbounded();
+    let mut state = ServerState::new(client, settings, background_sender, font_caches);
+    state.roots = roots;
+    state.trace = params.trace.unwrap_or_default();
+    let mut requests = ClientRequests::default();
+    if let Some(registrations) = registrations(&client) {
+        requests.send::<RegisterCapability>(&connection, registrations)?;
+    }
+    // Clients that support it are asked for the settings now and whenever
+    // they report a change, rather than relying on what they push.
+    let mut settings_request = None;
+    if client.configuration {
+        settings_request =
+            Some(requests.send::<WorkspaceConfiguration>(&connection, configuration_params())?);
+    }
+    let pool = WorkerPool::with_available_parallelism();
+    let in_flight = InFlight::default();
+    let generations = Generations::default();
+    let mut debouncer = Debouncer::new(DIAGNOSTICS_DELAY);
+    log::debug!("Starting main loop");
+    loop {
+        let timeout = debouncer.timeout().map_or_else(never, after);
+        let mut refresh = None;
+        let msg = select! {
+            recv(connection.receiver) -> msg => match msg {
+                Ok(msg) => Some(msg),
+                Err(_) => break,
+            },
+            recv(background) -> event => {
+                // The state holds a sender, so the channel stays open.
+                if let Ok(event) = event {
+                    refresh = handle_background(event, &mut state);
+                }
+                None
+            },
+            recv(timeout) -> _ => None,
+        };
+        for uri in debouncer.take_due() {
+            spawn_diagnostics(&pool, &connection, &state, &generations, uri);
+        }
+        if let Some(msg) = msg {
+            trace_message(&msg, &connection, &state);
+            refresh = match msg {
+                Message::Request(req) => {
+                    if connection.handle_shutdown(&req)? {
+                        return Ok(());
+                    }
+                    in_flight.start(req.id.clone());
+                    let snapshot = state.clone();
+                    let in_flight = in_flight.clone();
+                    let sender = connection.sender.clone();
+                    pool.spawn(move || {
+                        let res = handle_request(req, &snapshot);
+                        if in_flight.finish(&res.id) {
+                            let _ = sender.send(Message::Response(res));
+                        }
+                    });
+                    None
+                }
+                Message::Response(resp) if Some(&resp.id) == settings_request.as_ref() => {
+                    settings_request = None;
+                    match resp
+                        .result
+                        .map(serde_json::from_value::<Vec<serde_json::Value>>)
+                    {
+                        Some(Ok(mut sections)) if !sections.is_empty() => {
+                            state.apply_settings(Settings::from_value(sections.swap_remove(0)));
+                            Some(Refresh::All)
+                        }
+                        _ => {
+                            log::warn!("Could not get the settings: {:?}", resp.error);
+                            None
+                        }
+                    }
+                }
+                Message::Response(resp) => {
+                    if let Some(err) = resp.error {
+                        log::warn!("Request {} failed: {}", resp.id, err.message);
+                    }
+                    None
                 }
-                eprintln!("Got request: {req:?}");
-                if let Some(res) = handle_request(req, &mut doc) {
-                    connection.sender.send(Message::Response(res))?;
+                Message::Notification(notif) if notif.method == Cancel::METHOD => {
+                    let Ok(params) = notif.extract::<CancelParams>(Cancel::METHOD) else {
+                        continue;
+                    };
+                    let id = match params.id {
+                        NumberOrString::Number(id) => RequestId::from(id),
+                        NumberOrString::String(id) => RequestId::from(id),
+                    };
+                    if in_flight.finish(&id) {
+                        let res = Response::new_err(
+                            id,
+                            ErrorCode::RequestCanceled as i32,
+                            "request cancelled".to_string(),
+                        );
+                        connection.sender.send(Message::Response(res))?;
+                    }
+                    None
                 }
+                Message::Notification(notif) => {
+                    if client.configuration && notif.method == DidChangeConfiguration::METHOD {
+                        settings_request =
+                            Some(requests.send::<WorkspaceConfiguration>(
+                                &connection,
+                                configuration_params(),
+                            )?);
+                    }
+                    handle_notification(notif, &mut state)
+                }
+            };
+        }
+        match refresh {
+            // Clients that pull diagnostics ask for them when a document
+            // changes, but have to be told when the settings did.
+            Some(Refresh::All) if client.pull_diagnostics && client.diagnostic_refresh => {
+                requests.send::<WorkspaceDiagnosticRefresh>(&connection, ())?;
             }
-            Message::Response(resp) => {
-                eprintln!("Got response: {resp:?}");
+            _ if client.pull_diagnostics => {}
+            Some(Refresh::Now(uri)) => {
+                debouncer.cancel(&uri);
+                spawn_diagnostics(&pool, &connection, &state, &generations, uri);
             }
-            Message::Notification(notif) => {
-                handle_notification(notif, &mut doc);
+            Some(Refresh::Debounced(uri)) => debouncer.schedule(uri),
+            Some(Refresh::All) => {
+                for uri in state.docs.uris() {
+                    debouncer.cancel(uri);
+                    spawn_diagnostics(&pool, &connection, &state, &generations, uri.clone());
+                }
             }
+            None => {}
         }
     }
     Ok(())
 }
+
+/// Numbers the requests the server sends to the client.
+#[derive(Default)]
+struct ClientRequests {
+    next_id: i32,
+}
+
+impl ClientRequests {
+    /// Sends a request to the client and returns its id.
+    fn send<R>(
+        &mut self,
+        connection: &Connection,
+        params: R::Params,
+    ) -> Result<RequestId, Box<dyn Error + Sync + Send>>
+    where
+        R: lsp_types::request::Request,
+    {
+        self.next_id += 1;
+        let id = RequestId::from(format!("ghostty-lsp/{}", self.next_id));
+        let req = Request::new(id.clone(), R::METHOD.to_string(), params);
+        connection.sender.send(Message::Request(req))?;
+        Ok(id)
+    }
+}
+
+/// Logs a message from the client and reports it back as `$/logTrace`, with
+/// document contents redacted unless the log is set up to include them.
+fn trace_message(msg: &Message, connection: &Connection, state: &ServerState) {
+    let (kind, method, id, params) = match msg {
+        Message::Request(req) => ("request", req.method.as_str(), Some(&req.id), &req.params),
+        Message::Notification(notif) => {
+            ("notification", notif.method.as_str(), None, &notif.params)
+        }
+        Message::Response(resp) => {
+            log::debug!("Got response to {}", resp.id);
+            return;
+        }
+    };
+    let name = match id {
+        Some(id) => format!("'{method} - ({id})'"),
+        None => format!("'{method}'"),
+    };
+    log::debug!("Got {kind} {name}: {}", logging::loggable(params));
+    logging::log_trace(
+        &connection.sender,
+        state.trace,
+        || format!("Received {kind} {name}."),
+        || format!("Params: {}", logging::loggable(params)),
+    );
+}
+
+/// Publishes the diagnostics of `uri` from a worker, unless newer ones are
+/// computed first.
+fn spawn_diagnostics(
+    pool: &WorkerPool,
+    connection: &Connection,
+    state: &ServerState,
+    generations: &Generations,
+    uri: Url,
+) {
+    let generation = generations.next(&uri);
+    let snapshot = state.clone();
+    let generations = generations.clone();
+    let sender = connection.sender.clone();
+    pool.spawn(move || {
+        let notif = publish_diagnostics(&snapshot, uri.clone());
+        if generations.is_current(&uri, generation) {
+            let _ = sender.send(Message::Notification(notif));
+        }
+    });
+}
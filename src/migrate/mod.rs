@@ -0,0 +1,316 @@
+//! Migration of deprecated keys and values to their replacements.
+//!
+//! Ghostty keeps reading a renamed key or value for a while, so a
+//! deprecation is only reported, and migrated, once the Ghostty in use knows
+//! its replacement. Migrating renames the key and rewrites the values the
+//! replacement spells differently, leaving the rest of the line alone. Keys
+//! that only had values renamed keep their name.
+use std::collections::HashMap;
+
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, DiagnosticSeverity, DiagnosticTag,
+    NumberOrString, Range, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::definitions::{get_deprecation, Deprecation, Version};
+use crate::diagnostics::diagnostic;
+use crate::parser::{byte_offset, ConfigFile};
+
+/// The code of deprecation diagnostics.
+pub const DEPRECATED_KEY: &str = "deprecated-key";
+
+/// The kind of the action that migrates a whole document.
+pub const SOURCE_MIGRATE: CodeActionKind = CodeActionKind::new("source.migrate");
+
+/// The migration of one entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub deprecation: &'static Deprecation,
+    /// The range of the deprecated key, or of the value when only the value
+    /// is deprecated.
+    pub range: Range,
+    /// The value of the entry before migrating.
+    pub value: String,
+    /// Edits of the key and, when it changes, the value. They are on the
+    /// line of the entry and do not overlap.
+    pub edits: Vec<TextEdit>,
+}
+
+impl Migration {
+    pub fn message(&self) -> String {
+        let deprecation = self.deprecation;
+        if deprecation.renames() {
+            format!(
+                "`{}` is deprecated since Ghostty {}, use `{}` instead",
+                deprecation.name, deprecation.since, deprecation.replacement
+            )
+        } else {
+            format!(
+                "`{}` is deprecated for `{}` since Ghostty {}, use `{}` instead",
+                self.value,
+                deprecation.name,
+                deprecation.since,
+                deprecation.migrate_value(&self.value)
+            )
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let deprecation = self.deprecation;
+        if deprecation.renames() {
+            format!(
+                "Migrate `{}` to `{}`",
+                deprecation.name, deprecation.replacement
+            )
+        } else {
+            format!(
+                "Replace `{}` with `{}`",
+                self.value,
+                deprecation.migrate_value(&self.value)
+            )
+        }
+    }
+}
+
+/// The migrations of the deprecated keys and values in `config` that
+/// Ghostty `version` knows the replacement of.
+pub fn migrations(config: &ConfigFile, version: Version) -> Vec<Migration> {
+    config
+        .entries()
+        .filter_map(|entry| {
+            let deprecation = get_deprecation(&entry.key)?;
+            if !deprecation.applies_to(version) || !deprecation.deprecates(&entry.value) {
+                return None;
+            }
+            let value_range = entry.value_subrange(0, entry.value.len());
+            let mut edits = Vec::new();
+            if deprecation.renames() {
+                edits.push(TextEdit::new(
+                    entry.key_range,
+                    deprecation.replacement.to_string(),
+                ));
+            }
+            let value = deprecation.migrate_value(&entry.value);
+            if value != entry.value {
+                edits.push(TextEdit::new(value_range, value.to_string()));
+            }
+            Some(Migration {
+                deprecation,
+                range: if deprecation.renames() {
+                    entry.key_range
+                } else {
+                    value_range
+                },
+                value: entry.value.clone(),
+                edits,
+            })
+        })
+        .collect()
+}
+
+pub fn deprecation_diagnostics(config: &ConfigFile, version: Version) -> Vec<Diagnostic> {
+    migrations(config, version)
+        .into_iter()
+        .map(|migration| {
+            let mut diagnostic = diagnostic(
+                migration.range,
+                DiagnosticSeverity::WARNING,
+                DEPRECATED_KEY,
+                migration.message(),
+            );
+            diagnostic.tags = Some(vec![DiagnosticTag::DEPRECATED]);
+            diagnostic
+        })
+        .collect()
+}
+
+/// A quick fix for each deprecation on the lines of `range`, and an action
+/// migrating the whole document when it has any deprecation.
+/// `diagnostics` are the ones the client sent along, which the quick fixes
+/// resolve.
+pub fn migrate_actions(
+    uri: &Url,
+    config: &ConfigFile,
+    version: Version,
+    range: Range,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let migrations = migrations(config, version);
+    let workspace_edit = |edits: Vec<TextEdit>| WorkspaceEdit {
+        changes: Some(HashMap::from([(uri.clone(), edits)])),
+        ..Default::default()
+    };
+    let code = Some(NumberOrString::String(DEPRECATED_KEY.to_string()));
+    let mut actions = Vec::new();
+    for migration in &migrations {
+        let line = migration.range.start.line;
+        if line < range.start.line || line > range.end.line {
+            continue;
+        }
+        let fixed: Vec<Diagnostic> = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.code == code && diagnostic.range == migration.range)
+            .cloned()
+            .collect();
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: migration.title(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: (!fixed.is_empty()).then_some(fixed),
+            edit: Some(workspace_edit(migration.edits.clone())),
+            is_preferred: Some(true),
+            ..Default::default()
+        }));
+    }
+    if !migrations.is_empty() {
+        let edits = migrations
+            .into_iter()
+            .flat_map(|migration| migration.edits)
+            .collect();
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Migrate all deprecated keys and values".to_string(),
+            kind: Some(SOURCE_MIGRATE),
+            edit: Some(workspace_edit(edits)),
+            ..Default::default()
+        }));
+    }
+    actions
+}
+
+/// `text` with every deprecated key and value migrated.
+pub fn migrate(text: &str, version: Version) -> String {
+    let config = ConfigFile::parse(text);
+    let mut edits: HashMap<u32, Vec<TextEdit>> = HashMap::new();
+    for migration in migrations(&config, version) {
+        edits
+            .entry(migration.range.start.line)
+            .or_default()
+            .extend(migration.edits);
+    }
+    let mut migrated = String::with_capacity(text.len());
+    for (number, line) in text.split_inclusive('\n').enumerate() {
+        let Some(line_edits) = edits.get_mut(&(number as u32)) else {
+            migrated.push_str(line);
+            continue;
+        };
+        // Apply the edits from the end so the earlier columns stay valid.
+        line_edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start.character));
+        let mut line = line.to_string();
+        for edit in line_edits.iter() {
+            let start = byte_offset(&line, edit.range.start.character);
+            let end = byte_offset(&line, edit.range.end.character);
+            line.replace_range(start..end, &edit.new_text);
+        }
+        migrated.push_str(&line);
+    }
+    migrated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::LATEST;
+
+    #[test]
+    fn test_migrate() {
+        let text =
+            "background-blur-radius = 0\nbackground-blur-radius=\"20\" # blur\r\nfont-size = 12";
+        assert_eq!(
+            migrate(text, LATEST),
+            "background-blur = false\nbackground-blur=\"20\" # blur\r\nfont-size = 12"
+        );
+        assert_eq!(migrate(text, Version::new(1, 0, 1)), text);
+    }
+
+    #[test]
+    fn test_migrate_values() {
+        let text = "window-decoration = false\nwindow-decoration = server\n";
+        assert_eq!(
+            migrate(text, LATEST),
+            "window-decoration = none\nwindow-decoration = server\n"
+        );
+
+        let config = ConfigFile::parse(text);
+        let diagnostics = deprecation_diagnostics(&config, LATEST);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`false` is deprecated for `window-decoration` since Ghostty 1.1.0, use `none` instead"
+        );
+        assert_eq!(diagnostics[0].range.start.character, 20);
+        let uri = Url::parse("file:///config").unwrap();
+        let actions = migrate_actions(&uri, &config, LATEST, diagnostics[0].range, &diagnostics);
+        match &actions[0] {
+            CodeActionOrCommand::CodeAction(action) => {
+                assert_eq!(action.title, "Replace `false` with `none`")
+            }
+            CodeActionOrCommand::Command(_) => panic!("not an action"),
+        }
+    }
+
+    #[test]
+    fn test_migrate_non_native_fullscreen() {
+        let text =
+            "macos-non-native-fullscreen = true\nmacos-non-native-fullscreen = visible-menu\n";
+        assert_eq!(
+            migrate(text, LATEST),
+            "macos-non-native-fullscreen = padded-notch\nmacos-non-native-fullscreen = visible-menu\n"
+        );
+        assert_eq!(migrate(text, Version::new(1, 0, 1)), text);
+
+        let diagnostics = deprecation_diagnostics(&ConfigFile::parse(text), LATEST);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`true` is deprecated for `macos-non-native-fullscreen` since Ghostty 1.1.0, use `padded-notch` instead"
+        );
+        assert_eq!(diagnostics[0].range.start.character, 30);
+    }
+
+    #[test]
+    fn test_migrate_gtk_titlebar() {
+        let text = "gtk-titlebar = false\ngtk-titlebar=true\n";
+        assert_eq!(
+            migrate(text, LATEST),
+            "gtk-titlebar-style = tabs\ngtk-titlebar-style=native\n"
+        );
+        assert_eq!(migrate(text, Version::new(1, 1, 0)), text);
+
+        let diagnostics = deprecation_diagnostics(&ConfigFile::parse(text), LATEST);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].message,
+            "`gtk-titlebar` is deprecated since Ghostty 1.2.0, use `gtk-titlebar-style` instead"
+        );
+        assert!(
+            deprecation_diagnostics(&ConfigFile::parse(text), Version::new(1, 1, 0)).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_deprecation_diagnostics_and_actions() {
+        let uri = Url::parse("file:///config").unwrap();
+        let config = ConfigFile::parse("font-size = 12\nbackground-blur-radius = 0\n");
+        let diagnostics = deprecation_diagnostics(&config, LATEST);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`background-blur-radius` is deprecated since Ghostty 1.1.0, use `background-blur` instead"
+        );
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::DEPRECATED]));
+
+        let actions = migrate_actions(&uri, &config, LATEST, diagnostics[0].range, &diagnostics);
+        let kinds: Vec<_> = actions
+            .iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.kind.clone().unwrap(),
+                CodeActionOrCommand::Command(_) => panic!("not an action"),
+            })
+            .collect();
+        assert_eq!(kinds, vec![CodeActionKind::QUICKFIX, SOURCE_MIGRATE]);
+        let first_line = Range::default();
+        assert_eq!(
+            migrate_actions(&uri, &config, LATEST, first_line, &[]).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! A parser for Ghostty configuration files.
+//!
+//! Ghostty configuration is line based. Each line is either blank, a comment
+//! starting with `#`, or a `key = value` entry. Values may optionally be
+//! wrapped in double quotes which are not part of the value.
+use lsp_types::{Position, Range};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub line: u32,
+    pub key: String,
+    pub key_range: Range,
+    /// The value with surrounding quotes removed.
+    pub value: String,
+    /// The range of the value as written, including any quotes.
+    pub value_range: Range,
+    /// Whether the value was wrapped in double quotes.
+    pub quoted: bool,
+}
+
+impl Entry {
+    /// Returns the column of the byte offset `idx` within `value`.
+    pub fn value_column(&self, idx: usize) -> u32 {
+        let quote = u32::from(self.quoted);
+        self.value_range.start.character + quote + utf16_len(&self.value[..idx])
+    }
+
+    /// Returns the byte offset within `value` of the UTF-16 `column`, or
+    /// `None` when the column is outside the value.
+    pub fn value_offset(&self, column: u32) -> Option<usize> {
+        let start = self.value_column(0);
+        if column < start || column > self.value_column(self.value.len()) {
+            return None;
+        }
+        Some(byte_offset(&self.value, column - start))
+    }
+
+    /// Returns the range covering `value[start..end]`.
+    pub fn value_subrange(&self, start: usize, end: usize) -> Range {
+        Range::new(
+            Position::new(self.line, self.value_column(start)),
+            Position::new(self.line, self.value_column(end)),
+        )
+    }
+
+    /// The range of the whole entry, from the start of the key to the end of
+    /// the value.
+    pub fn range(&self) -> Range {
+        Range::new(self.key_range.start, self.value_range.end)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    Entry(Entry),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub number: u32,
+    pub text: String,
+    pub kind: LineKind,
+}
+
+/// An inclusive range of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+    pub lines: Vec<Line>,
+}
+
+impl ConfigFile {
+    pub fn parse(text: &str) -> Self {
+        let lines = text
+            .lines()
+            .enumerate()
+            .map(|(number, text)| parse_line(number as u32, text))
+            .collect();
+        Self { lines }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.lines.iter().filter_map(|line| match &line.kind {
+            LineKind::Entry(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    pub fn entry_at_line(&self, line: u32) -> Option<&Entry> {
+        match &self.lines.get(line as usize)?.kind {
+            LineKind::Entry(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Runs of non-blank lines separated by blank lines.
+    pub fn sections(&self) -> Vec<Block> {
+        self.blocks(|line| line.kind != LineKind::Blank)
+    }
+
+    /// Runs of consecutive comment lines.
+    pub fn comment_blocks(&self) -> Vec<Block> {
+        self.blocks(|line| line.kind == LineKind::Comment)
+    }
+
+    fn blocks(&self, include: impl Fn(&Line) -> bool) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for line in self.lines.iter().filter(|line| include(line)) {
+            match blocks.last_mut() {
+                Some(block) if block.end + 1 == line.number => block.end = line.number,
+                _ => blocks.push(Block {
+                    start: line.number,
+                    end: line.number,
+                }),
+            }
+        }
+        blocks
+    }
+
+    /// The range of a whole line, from the first to the last character.
+    pub fn line_range(&self, line: u32) -> Range {
+        let len = self
+            .lines
+            .get(line as usize)
+            .map(|line| utf16_len(&line.text))
+            .unwrap_or(0);
+        Range::new(Position::new(line, 0), Position::new(line, len))
+    }
+
+    /// The range spanning the lines of `block`.
+    pub fn block_range(&self, block: Block) -> Range {
+        Range::new(
+            self.line_range(block.start).start,
+            self.line_range(block.end).end,
+        )
+    }
+}
+
+fn parse_line(number: u32, text: &str) -> Line {
+    let trimmed = text.trim();
+    let kind = if trimmed.is_empty() {
+        LineKind::Blank
+    } else if trimmed.starts_with('#') {
+        LineKind::Comment
+    } else {
+        LineKind::Entry(parse_entry(number, text))
+    };
+    Line {
+        number,
+        text: text.to_string(),
+        kind,
+    }
+}
+
+fn parse_entry(number: u32, text: &str) -> Entry {
+    let (key_part, value_part, value_offset) = match text.find('=') {
+        Some(idx) => (&text[..idx], &text[idx + 1..], idx + 1),
+        None => (text, "", text.len()),
+    };
+
+    let key_start = key_part.len() - key_part.trim_start().len();
+    let key = key_part.trim();
+    let key_range = Range::new(
+        Position::new(number, utf16_len(&text[..key_start])),
+        Position::new(number, utf16_len(&text[..key_start + key.len()])),
+    );
+
+    let value_start = value_offset + value_part.len() - value_part.trim_start().len();
+    let raw_value = value_part.trim();
+    let value_range = Range::new(
+        Position::new(number, utf16_len(&text[..value_start])),
+        Position::new(number, utf16_len(&text[..value_start + raw_value.len()])),
+    );
+
+    let quoted = raw_value.len() >= 2 && raw_value.starts_with('"') && raw_value.ends_with('"');
+    let value = if quoted {
+        &raw_value[1..raw_value.len() - 1]
+    } else {
+        raw_value
+    };
+
+    Entry {
+        line: number,
+        key: key.to_string(),
+        key_range,
+        value: value.to_string(),
+        value_range,
+        quoted,
+    }
+}
+
+/// The length of `s` in UTF-16 code units, which is how LSP positions count
+/// characters.
+pub fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// Converts a UTF-16 column on `line` into a byte offset, clamped to the end
+/// of the line.
+pub fn byte_offset(line: &str, column: u32) -> usize {
+    let mut units = 0;
+    for (idx, ch) in line.char_indices() {
+        if units >= column {
+            return idx;
+        }
+        units += ch.len_utf16() as u32;
+    }
+    line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let config = ConfigFile::parse("# comment\n\nfont-size = 12\n");
+        assert_eq!(config.lines.len(), 3);
+        assert_eq!(config.lines[0].kind, LineKind::Comment);
+        assert_eq!(config.lines[1].kind, LineKind::Blank);
+        let entry = config.entry_at_line(2).unwrap();
+        assert_eq!(entry.key, "font-size");
+        assert_eq!(entry.value, "12");
+        assert_eq!(entry.key_range.start.character, 0);
+        assert_eq!(entry.key_range.end.character, 9);
+        assert_eq!(entry.value_range.start.character, 12);
+        assert_eq!(entry.value_range.end.character, 14);
+    }
+
+    #[test]
+    fn test_parse_quoted_value() {
+        let config = ConfigFile::parse("  title = \"my term\"  ");
+        let entry = config.entry_at_line(0).unwrap();
+        assert_eq!(entry.key, "title");
+        assert_eq!(entry.key_range.start.character, 2);
+        assert_eq!(entry.value, "my term");
+        assert!(entry.quoted);
+        assert_eq!(entry.value_range.start.character, 10);
+        assert_eq!(entry.value_range.end.character, 19);
+        assert_eq!(entry.value_column(3), 14);
+        assert_eq!(entry.value_offset(14), Some(3));
+        assert_eq!(entry.value_offset(9), None);
+    }
+
+    #[test]
+    fn test_parse_value_with_equals() {
+        let config = ConfigFile::parse("keybind = ctrl+a=reload_config");
+        let entry = config.entry_at_line(0).unwrap();
+        assert_eq!(entry.key, "keybind");
+        assert_eq!(entry.value, "ctrl+a=reload_config");
+    }
+
+    #[test]
+    fn test_parse_key_without_value() {
+        let config = ConfigFile::parse("fullscreen");
+        let entry = config.entry_at_line(0).unwrap();
+        assert_eq!(entry.key, "fullscreen");
+        assert_eq!(entry.value, "");
+    }
+
+    #[test]
+    fn test_sections_and_comment_blocks() {
+        let config = ConfigFile::parse("# a\n# b\nfont-size = 1\n\n\n# c\ntheme = x\n");
+        assert_eq!(
+            config.sections(),
+            vec![Block { start: 0, end: 2 }, Block { start: 5, end: 6 }]
+        );
+        assert_eq!(
+            config.comment_blocks(),
+            vec![Block { start: 0, end: 1 }, Block { start: 5, end: 5 }]
+        );
+    }
+
+    #[test]
+    fn test_utf16_columns() {
+        let config = ConfigFile::parse("title = 😀 x");
+        let entry = config.entry_at_line(0).unwrap();
+        assert_eq!(entry.value_range.end.character, 12);
+        assert_eq!(byte_offset("title = 😀 x", 10), 12);
+    }
+}
@@ -0,0 +1,121 @@
+//! Helpers for resolving paths written in configuration values.
+use std::path::{Path, PathBuf};
+
+/// Expands a leading `~` to the user's home directory.
+pub fn expand_home(value: &str) -> PathBuf {
+    if value == "~" {
+        if let Some(home) = home_dir() {
+            return home;
+        }
+    }
+    if let Some(rest) = value.strip_prefix("~/") {
+        if let Some(home) = home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(value)
+}
+
+/// Resolves `value` the way Ghostty does: `~` is expanded and relative paths
+/// are relative to `base_dir`, usually the directory of the file that
+/// contains the value.
+pub fn resolve(base_dir: &Path, value: &str) -> PathBuf {
+    let path = expand_home(value);
+    if path.is_absolute() {
+        path
+    } else {
+        base_dir.join(path)
+    }
+}
+
+/// Expands `$VAR` and `${VAR}` references to environment variables. Unset
+/// variables expand to nothing and their names are returned alongside the
+/// expanded value.
+pub fn expand_env(value: &str) -> (String, Vec<String>) {
+    let mut expanded = String::with_capacity(value.len());
+    let mut missing = Vec::new();
+    let mut rest = value;
+    while let Some(idx) = rest.find('$') {
+        expanded.push_str(&rest[..idx]);
+        rest = &rest[idx + 1..];
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            continue;
+        }
+        match std::env::var(name) {
+            Ok(var) => expanded.push_str(&var),
+            Err(_) => missing.push(name.to_string()),
+        }
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+    (expanded, missing)
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The XDG base directory in `var`, falling back to `fallback` below the
+/// home directory.
+pub fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|home| home.join(fallback)),
+    }
+}
+
+/// The configuration file Ghostty loads by default.
+pub fn default_config_path() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join("ghostty/config"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_relative_and_absolute() {
+        let base = Path::new("/etc/ghostty");
+        assert_eq!(resolve(base, "extra"), PathBuf::from("/etc/ghostty/extra"));
+        assert_eq!(resolve(base, "/tmp/extra"), PathBuf::from("/tmp/extra"));
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = home_dir().unwrap();
+        assert_eq!(expand_home("~/config"), home.join("config"));
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("a/~"), PathBuf::from("a/~"));
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            expand_env("$HOME/a/${HOME}"),
+            (format!("{home}/a/{home}"), Vec::new())
+        );
+        assert_eq!(
+            expand_env("/x/$GHOSTTY_LSP_UNSET_VAR/y$"),
+            (
+                "/x//y$".to_string(),
+                vec!["GHOSTTY_LSP_UNSET_VAR".to_string()]
+            )
+        );
+    }
+}
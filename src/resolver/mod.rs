@@ -0,0 +1,303 @@
+//! The effective configuration: what Ghostty ends up using once every file
+//! has been loaded.
+//!
+//! The root file is read first, then its `config-file` includes in the
+//! order [`collect_includes`] returns them. When a `theme` is set, the theme
+//! file is applied first and the configuration files on top of it, so values
+//! from the configuration always win over the theme. Within that order the
+//! last value of a key wins, repeatable keys accumulate, an empty value
+//! resets a key to its default and `keybind = clear` removes every keybind
+//! set so far.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Location, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::definitions::{get_config_key, CONFIG_KEYS};
+use crate::documents::Documents;
+use crate::includes::collect_includes;
+use crate::parser::ConfigFile;
+use crate::paths;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveValue {
+    pub key: String,
+    pub value: String,
+    /// The entry that set the value, or `None` for defaults.
+    pub location: Option<Location>,
+}
+
+/// The `ghostty/effectiveConfig` request resolves the configuration rooted
+/// at a document.
+pub enum EffectiveConfig {}
+
+impl lsp_types::request::Request for EffectiveConfig {
+    type Params = EffectiveConfigParams;
+    type Result = EffectiveConfigResult;
+    const METHOD: &'static str = "ghostty/effectiveConfig";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfigParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveConfigResult {
+    pub values: Vec<EffectiveValue>,
+}
+
+/// The directories theme names are looked up in, in order.
+pub fn theme_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(config_home) = paths::xdg_dir("XDG_CONFIG_HOME", ".config") {
+        dirs.push(config_home.join("ghostty/themes"));
+    }
+    if let Some(resources) = std::env::var_os("GHOSTTY_RESOURCES_DIR") {
+        dirs.push(PathBuf::from(resources).join("themes"));
+    }
+    dirs.push(PathBuf::from("/usr/share/ghostty/themes"));
+    dirs.push(PathBuf::from("/usr/local/share/ghostty/themes"));
+    dirs.push(PathBuf::from(
+        "/Applications/Ghostty.app/Contents/Resources/ghostty/themes",
+    ));
+    dirs
+}
+
+/// Finds the file of a `theme` value. Absolute paths are used as-is and
+/// names are looked up in `dirs`. For `light:A,dark:B` values the variant
+/// matching `window_theme` is used, the light one unless it is `dark`.
+pub fn resolve_theme(value: &str, window_theme: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let name = if value.contains(':') {
+        let wanted = if window_theme == "dark" {
+            "dark"
+        } else {
+            "light"
+        };
+        value
+            .split(',')
+            .filter_map(|pair| pair.split_once(':'))
+            .find(|(variant, _)| variant.trim() == wanted)
+            .map(|(_, name)| name.trim())?
+    } else {
+        value
+    };
+    let path = paths::expand_home(name);
+    if path.is_absolute() {
+        return Some(path);
+    }
+    dirs.iter()
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The values of each key in the order they were applied.
+#[derive(Debug, Default)]
+struct Values {
+    values: HashMap<String, Vec<EffectiveValue>>,
+    /// Keys in the order they were first set.
+    order: Vec<String>,
+}
+
+impl Values {
+    fn apply(&mut self, path: &Path, config: &ConfigFile) {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return;
+        };
+        for entry in config.entries() {
+            let schema = get_config_key(&entry.key);
+            let repeatable = schema.is_some_and(|key| key.repeatable);
+            if !self.values.contains_key(&entry.key) {
+                self.order.push(entry.key.clone());
+            }
+            let values = self.values.entry(entry.key.clone()).or_default();
+            let location = Some(Location::new(uri.clone(), entry.range()));
+            let reset =
+                entry.value.is_empty() || (entry.key == "keybind" && entry.value == "clear");
+            if reset {
+                values.clear();
+                // The reset itself is where a scalar's default came from.
+                if let Some(default) = schema.filter(|_| !repeatable).and_then(|key| key.default) {
+                    values.push(EffectiveValue {
+                        key: entry.key.clone(),
+                        value: default.to_string(),
+                        location,
+                    });
+                }
+                continue;
+            }
+            if !repeatable {
+                values.clear();
+            }
+            values.push(EffectiveValue {
+                key: entry.key.clone(),
+                value: entry.value.clone(),
+                location,
+            });
+        }
+    }
+
+    fn last(&self, key: &str) -> Option<&str> {
+        self.values
+            .get(key)?
+            .last()
+            .map(|value| value.value.as_str())
+    }
+}
+
+/// Resolves the configuration rooted at `root`, whose contents are
+/// `config`. Keys that are not set anywhere have their default value
+/// without a location. Known keys come first, in schema order.
+pub fn resolve_config(
+    docs: &Documents,
+    root: &Path,
+    config: &ConfigFile,
+    theme_dirs: &[PathBuf],
+) -> Vec<EffectiveValue> {
+    let mut files = vec![(root.to_path_buf(), config.clone())];
+    files.extend(
+        collect_includes(docs, root, config)
+            .into_iter()
+            .map(|included| (included.path, included.config)),
+    );
+    let mut values = Values::default();
+    for (path, config) in &files {
+        values.apply(path, config);
+    }
+
+    let window_theme = values.last("window-theme").unwrap_or("system");
+    let theme = values
+        .last("theme")
+        .and_then(|theme| resolve_theme(theme, window_theme, theme_dirs))
+        .and_then(|path| Some((docs.read(&path)?, path)));
+    if let Some((text, path)) = theme {
+        let mut themed = Values::default();
+        themed.apply(&path, &ConfigFile::parse(&text));
+        for (path, config) in &files {
+            themed.apply(path, config);
+        }
+        values = themed;
+    }
+
+    let mut resolved = Vec::new();
+    for key in CONFIG_KEYS.iter() {
+        match values.values.remove(key.name) {
+            Some(set) => resolved.extend(set),
+            None => resolved.extend(key.default.map(|default| EffectiveValue {
+                key: key.name.to_string(),
+                value: default.to_string(),
+                location: None,
+            })),
+        }
+    }
+    for key in &values.order {
+        resolved.extend(values.values.remove(key).unwrap_or_default());
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(docs: &mut Documents, path: &str, text: &str) {
+        docs.open(Url::from_file_path(path).unwrap(), text.to_string());
+    }
+
+    fn resolved(docs: &Documents, text: &str, key: &str) -> Vec<(String, Option<String>)> {
+        let config = ConfigFile::parse(text);
+        resolve_config(
+            docs,
+            Path::new("/virtual/config"),
+            &config,
+            &[PathBuf::from("/virtual/themes")],
+        )
+        .into_iter()
+        .filter(|value| value.key == key)
+        .map(|value| {
+            let location = value.location.map(|location| {
+                format!("{}:{}", location.uri.path(), location.range.start.line + 1)
+            });
+            (value.value, location)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_last_value_wins_across_includes() {
+        let mut docs = Documents::default();
+        open(&mut docs, "/virtual/extra", "font-size = 16\n");
+        let text = "config-file = extra\nfont-size = 13\nfont-size = 14\n";
+        assert_eq!(
+            resolved(&docs, text, "font-size"),
+            vec![("16".to_string(), Some("/virtual/extra:1".to_string()))]
+        );
+        assert_eq!(
+            resolved(&docs, "", "font-size"),
+            vec![("12".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_repeatable_keys_accumulate_and_reset() {
+        let docs = Documents::default();
+        let text = "keybind = a=ignore\nkeybind = clear\nkeybind = b=ignore\nkeybind = c=ignore\nfont-family = A\nfont-family =\nfont-family = B\n";
+        assert_eq!(
+            resolved(&docs, text, "keybind"),
+            vec![
+                (
+                    "b=ignore".to_string(),
+                    Some("/virtual/config:3".to_string())
+                ),
+                (
+                    "c=ignore".to_string(),
+                    Some("/virtual/config:4".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            resolved(&docs, text, "font-family"),
+            vec![("B".to_string(), Some("/virtual/config:7".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_empty_value_resets_to_default() {
+        let docs = Documents::default();
+        assert_eq!(
+            resolved(&docs, "background = #000000\nbackground =\n", "background"),
+            vec![("#282c34".to_string(), Some("/virtual/config:2".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_theme_is_applied_under_the_config() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-themes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let theme_path = dir.join("Night");
+        std::fs::write(&theme_path, "background = #111111\nforeground = #eeeeee\n").unwrap();
+
+        let config = ConfigFile::parse(
+            "theme = light:Day,dark:Night\nwindow-theme = dark\nforeground = #000000\n",
+        );
+        let values = resolve_config(
+            &Documents::default(),
+            Path::new("/virtual/config"),
+            &config,
+            std::slice::from_ref(&dir),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        let value = |key: &str| {
+            let value = values.iter().find(|value| value.key == key).unwrap();
+            let path = value.location.as_ref().unwrap().uri.to_file_path().unwrap();
+            (value.value.clone(), path)
+        };
+        assert_eq!(value("background"), ("#111111".to_string(), theme_path));
+        assert_eq!(
+            value("foreground"),
+            ("#000000".to_string(), PathBuf::from("/virtual/config"))
+        );
+    }
+}
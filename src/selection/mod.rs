@@ -0,0 +1,93 @@
+//! Selection ranges that expand from a value to its entry and then to the
+//! surrounding section.
+use lsp_types::{Position, Range, SelectionRange};
+
+use crate::parser::{ConfigFile, LineKind};
+
+pub fn selection_ranges(config: &ConfigFile, positions: &[Position]) -> Vec<SelectionRange> {
+    positions
+        .iter()
+        .map(|position| selection_range(config, *position))
+        .collect()
+}
+
+fn selection_range(config: &ConfigFile, position: Position) -> SelectionRange {
+    let mut ranges = Vec::new();
+    if let Some(line) = config.lines.get(position.line as usize) {
+        match &line.kind {
+            LineKind::Entry(entry) => {
+                if contains(entry.value_range, position) {
+                    ranges.push(entry.value_range);
+                } else if contains(entry.key_range, position) {
+                    ranges.push(entry.key_range);
+                }
+                ranges.push(entry.range());
+            }
+            LineKind::Comment => {
+                ranges.push(config.line_range(line.number));
+                if let Some(block) = config
+                    .comment_blocks()
+                    .into_iter()
+                    .find(|block| block.start <= line.number && line.number <= block.end)
+                {
+                    ranges.push(config.block_range(block));
+                }
+            }
+            LineKind::Blank => {}
+        }
+        if let Some(section) = config
+            .sections()
+            .into_iter()
+            .find(|block| block.start <= line.number && line.number <= block.end)
+        {
+            ranges.push(config.block_range(section));
+        }
+    }
+    if !config.lines.is_empty() {
+        let last = config.lines.len() as u32 - 1;
+        ranges.push(Range::new(Position::new(0, 0), config.line_range(last).end));
+    }
+    ranges.dedup();
+
+    let mut selection: Option<SelectionRange> = None;
+    for range in ranges.into_iter().rev() {
+        selection = Some(SelectionRange {
+            range,
+            parent: selection.map(Box::new),
+        });
+    }
+    selection.unwrap_or(SelectionRange {
+        range: Range::new(position, position),
+        parent: None,
+    })
+}
+
+fn contains(range: Range, position: Position) -> bool {
+    range.start <= position && position <= range.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_expands_value_entry_section() {
+        let config = ConfigFile::parse("font-size = 12\ntheme = dark\n\ntitle = x\n");
+        let selection = selection_range(&config, Position::new(1, 9));
+        let mut ranges = vec![selection.range];
+        let mut parent = selection.parent;
+        while let Some(range) = parent {
+            ranges.push(range.range);
+            parent = range.parent;
+        }
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(Position::new(1, 8), Position::new(1, 12)),
+                Range::new(Position::new(1, 0), Position::new(1, 12)),
+                Range::new(Position::new(0, 0), Position::new(1, 12)),
+                Range::new(Position::new(0, 0), Position::new(3, 9)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Settings the client passes to the server.
+//!
+//! They arrive as `initializationOptions`, are pulled from the `ghostty`
+//! section with `workspace/configuration` when the client supports it, and
+//! can be pushed in `workspace/didChangeConfiguration`. All fields are
+//! optional and written in camelCase:
+//!
+//! ```json
+//! {
+//!   "platform": "macos",
+//!   "ghosttyVersion": "1.1.3",
+//!   "themeDirs": ["~/src/themes"],
+//!   "fontDirs": [],
+//!   "diagnostics": { "severity": { "missing-path": "hint", "invalid-command": "off" } },
+//!   "format": { "align": true },
+//!   "features": { "inlayHints": false }
+//! }
+//! ```
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use lsp_types::{ConfigurationItem, ConfigurationParams, Diagnostic, DiagnosticSeverity};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::definitions::{Platform, Version};
+use crate::formatting::FormatOptions;
+use crate::paths;
+
+/// The section settings are read from in `workspace/configuration` and
+/// `workspace/didChangeConfiguration`.
+pub const SECTION: &str = "ghostty";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// The platform Ghostty runs on. Defaults to the one the server runs on.
+    pub platform: Option<Platform>,
+    /// The Ghostty version the configuration is written for. Defaults to
+    /// the version of the `ghostty` on `PATH`.
+    pub ghostty_version: Option<Version>,
+    /// Directories searched for themes before the default ones.
+    pub theme_dirs: Vec<PathBuf>,
+    /// Directories searched for fonts in addition to the system ones.
+    pub font_dirs: Vec<PathBuf>,
+    pub diagnostics: DiagnosticSettings,
+    /// The formatting style used when there is no `.ghostty-lsp.toml` next
+    /// to the configuration file.
+    pub format: Option<FormatOptions>,
+    pub features: Features,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DiagnosticSettings {
+    /// The severity of each rule, by its diagnostic code.
+    pub severity: HashMap<String, Severity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Drops the rule's diagnostics.
+    Off,
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// The language features the server provides. All are on by default.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Features {
+    pub diagnostics: bool,
+    pub completion: bool,
+    pub hover: bool,
+    pub inlay_hints: bool,
+    pub signature_help: bool,
+    pub formatting: bool,
+    pub document_links: bool,
+    pub code_actions: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            diagnostics: true,
+            completion: true,
+            hover: true,
+            inlay_hints: true,
+            signature_help: true,
+            formatting: true,
+            document_links: true,
+            code_actions: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads settings from JSON. `null` gives the defaults, and so does a
+    /// malformed value, after logging why it was rejected.
+    pub fn from_value(value: Value) -> Self {
+        if value.is_null() {
+            return Self::default();
+        }
+        serde_json::from_value(value).unwrap_or_else(|err| {
+            log::warn!("Ignoring invalid settings: {err}");
+            Self::default()
+        })
+    }
+
+    /// The settings in the `ghostty` section of a
+    /// `workspace/didChangeConfiguration` payload, if it has one.
+    pub fn from_section(value: &Value) -> Option<Self> {
+        value.get(SECTION).cloned().map(Self::from_value)
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform.unwrap_or(if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else {
+            Platform::Linux
+        })
+    }
+
+    /// The configured theme directories with `~` expanded, followed by
+    /// `defaults`.
+    pub fn theme_dirs(&self, defaults: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut dirs = expand(&self.theme_dirs);
+        dirs.extend(defaults);
+        dirs
+    }
+
+    /// `defaults` followed by the configured font directories with `~`
+    /// expanded.
+    pub fn font_dirs(&self, mut defaults: Vec<PathBuf>) -> Vec<PathBuf> {
+        defaults.extend(expand(&self.font_dirs));
+        defaults
+    }
+
+    /// Applies the severity overrides to `diagnostics`, dropping those of
+    /// rules that are turned off.
+    pub fn apply_severity(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let code = match &diagnostic.code {
+                    Some(lsp_types::NumberOrString::String(code)) => code.as_str(),
+                    _ => return Some(diagnostic),
+                };
+                diagnostic.severity = match self.diagnostics.severity.get(code) {
+                    None => return Some(diagnostic),
+                    Some(Severity::Off) => return None,
+                    Some(Severity::Error) => Some(DiagnosticSeverity::ERROR),
+                    Some(Severity::Warning) => Some(DiagnosticSeverity::WARNING),
+                    Some(Severity::Information) => Some(DiagnosticSeverity::INFORMATION),
+                    Some(Severity::Hint) => Some(DiagnosticSeverity::HINT),
+                };
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+fn expand(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    dirs.iter()
+        .map(|dir| match dir.to_str() {
+            Some(dir) => paths::expand_home(dir),
+            None => dir.clone(),
+        })
+        .collect()
+}
+
+/// The params of the `workspace/configuration` request for the `ghostty`
+/// section.
+pub fn configuration_params() -> ConfigurationParams {
+    ConfigurationParams {
+        items: vec![ConfigurationItem {
+            scope_uri: None,
+            section: Some(SECTION.to_string()),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::diagnostic;
+    use lsp_types::Range;
+
+    #[test]
+    fn test_from_value() {
+        let settings = Settings::from_value(serde_json::json!({
+            "platform": "macos",
+            "ghosttyVersion": "1.1.3",
+            "themeDirs": ["/themes"],
+            "diagnostics": { "severity": { "missing-path": "off" } },
+            "format": { "align": true },
+            "features": { "inlayHints": false },
+        }));
+        assert_eq!(settings.platform(), Platform::MacOS);
+        assert_eq!(settings.ghostty_version, Some(Version::new(1, 1, 3)));
+        assert_eq!(
+            settings.theme_dirs(vec![PathBuf::from("/default")]),
+            vec![PathBuf::from("/themes"), PathBuf::from("/default")]
+        );
+        assert_eq!(
+            settings.diagnostics.severity.get("missing-path"),
+            Some(&Severity::Off)
+        );
+        assert!(settings.format.unwrap().align);
+        assert!(!settings.features.inlay_hints);
+        assert!(settings.features.hover);
+
+        assert_eq!(Settings::from_value(Value::Null), Settings::default());
+        assert_eq!(
+            Settings::from_value(serde_json::json!({ "platform": "windows" })),
+            Settings::default()
+        );
+        assert_eq!(
+            Settings::from_value(serde_json::json!({ "ghosttyVersion": "latest" })),
+            Settings::default()
+        );
+        assert_eq!(
+            Settings::from_section(&serde_json::json!({ "ghostty": { "fontDirs": ["/f"] } }))
+                .map(|settings| settings.font_dirs),
+            Some(vec![PathBuf::from("/f")])
+        );
+        assert_eq!(Settings::from_section(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn test_apply_severity() {
+        let settings = Settings::from_value(serde_json::json!({
+            "diagnostics": { "severity": { "missing-path": "off", "invalid-command": "hint" } },
+        }));
+        let diagnostics = vec![
+            diagnostic(
+                Range::default(),
+                DiagnosticSeverity::WARNING,
+                "missing-path",
+                String::new(),
+            ),
+            diagnostic(
+                Range::default(),
+                DiagnosticSeverity::WARNING,
+                "invalid-command",
+                String::new(),
+            ),
+            diagnostic(
+                Range::default(),
+                DiagnosticSeverity::ERROR,
+                "invalid-link-regex",
+                String::new(),
+            ),
+        ];
+        let severities: Vec<_> = settings
+            .apply_severity(diagnostics)
+            .into_iter()
+            .map(|diagnostic| diagnostic.severity)
+            .collect();
+        assert_eq!(
+            severities,
+            vec![
+                Some(DiagnosticSeverity::HINT),
+                Some(DiagnosticSeverity::ERROR)
+            ]
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! A lightweight check of `custom-shader` files.
+//!
+//! Ghostty runs custom shaders with the Shadertoy conventions: the shader
+//! defines `mainImage` and reads the Shadertoy uniforms, which Ghostty
+//! declares in a prefix it adds to the shader. Only the structure is checked
+//! here; the shader is not compiled.
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range, Url,
+};
+
+use crate::diagnostics::diagnostic;
+use crate::documents::Documents;
+use crate::links::{base_dir, path_values};
+use crate::parser::{utf16_len, ConfigFile};
+
+/// The uniforms Ghostty declares for custom shaders.
+pub const PROVIDED_UNIFORMS: [&str; 16] = [
+    "iResolution",
+    "iTime",
+    "iTimeDelta",
+    "iFrameRate",
+    "iFrame",
+    "iChannelTime",
+    "iChannelResolution",
+    "iMouse",
+    "iChannel0",
+    "iDate",
+    "iSampleRate",
+    "iCurrentCursor",
+    "iPreviousCursor",
+    "iCurrentCursorColor",
+    "iPreviousCursorColor",
+    "iTimeCursorChange",
+];
+
+const MAIN_IMAGE: &str = "void mainImage(out vec4 fragColor, in vec2 fragCoord)";
+
+/// GLSL types that can start a declaration.
+const TYPES: [&str; 30] = [
+    "void",
+    "bool",
+    "int",
+    "uint",
+    "float",
+    "double",
+    "vec2",
+    "vec3",
+    "vec4",
+    "bvec2",
+    "bvec3",
+    "bvec4",
+    "ivec2",
+    "ivec3",
+    "ivec4",
+    "uvec2",
+    "uvec3",
+    "uvec4",
+    "dvec2",
+    "dvec3",
+    "dvec4",
+    "mat2",
+    "mat3",
+    "mat4",
+    "mat2x2",
+    "mat3x3",
+    "mat4x4",
+    "sampler2D",
+    "sampler3D",
+    "samplerCube",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderProblem {
+    /// Byte offsets of the problem within the shader.
+    pub span: (usize, usize),
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Punct(char),
+}
+
+/// Checks the structure of the shader `source`.
+pub fn check_shader(source: &str) -> Vec<ShaderProblem> {
+    let code = strip_comments(source);
+    let tokens = tokenize(&code);
+    let mut problems = Vec::new();
+    problems.extend(check_brackets(&tokens));
+    problems.extend(check_main_image(&tokens));
+    problems.extend(check_uniforms(&tokens));
+    problems
+}
+
+/// Replaces comments with spaces, keeping byte offsets and line breaks.
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let blank = |code: &mut String, c: char| {
+        if c == '\n' {
+            code.push('\n');
+        } else {
+            code.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                code.push(' ');
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    blank(&mut code, next);
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                code.push(' ');
+                // The `*` opens the comment, so it cannot also close it.
+                chars.next();
+                code.push(' ');
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    blank(&mut code, next);
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => code.push(c),
+        }
+    }
+    code
+}
+
+fn tokenize(code: &str) -> Vec<(usize, Token<'_>)> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start + 1;
+            while let Some(&(idx, next)) = chars.peek() {
+                if !(next.is_ascii_alphanumeric() || next == '_') {
+                    break;
+                }
+                end = idx + 1;
+                chars.next();
+            }
+            tokens.push((start, Token::Ident(&code[start..end])));
+        } else if c.is_ascii_digit() {
+            // Numbers, including suffixes like `1.0f` or `0x1Fu`.
+            while chars
+                .peek()
+                .is_some_and(|(_, next)| next.is_ascii_alphanumeric() || *next == '.')
+            {
+                chars.next();
+            }
+        } else if !c.is_whitespace() {
+            tokens.push((start, Token::Punct(c)));
+        }
+    }
+    tokens
+}
+
+fn problem(
+    start: usize,
+    len: usize,
+    severity: DiagnosticSeverity,
+    message: String,
+) -> ShaderProblem {
+    ShaderProblem {
+        span: (start, start + len),
+        severity,
+        message,
+    }
+}
+
+/// Reports the first unbalanced bracket.
+fn check_brackets(tokens: &[(usize, Token)]) -> Option<ShaderProblem> {
+    let mut open: Vec<(usize, char)> = Vec::new();
+    for &(offset, token) in tokens {
+        let Token::Punct(c) = token else {
+            continue;
+        };
+        let expected = match c {
+            '(' | '[' | '{' => {
+                open.push((offset, c));
+                continue;
+            }
+            ')' => '(',
+            ']' => '[',
+            '}' => '{',
+            _ => continue,
+        };
+        match open.pop() {
+            Some((_, opened)) if opened == expected => {}
+            _ => {
+                return Some(problem(
+                    offset,
+                    1,
+                    DiagnosticSeverity::ERROR,
+                    format!("unmatched `{c}`"),
+                ));
+            }
+        }
+    }
+    open.pop().map(|(offset, c)| {
+        problem(
+            offset,
+            1,
+            DiagnosticSeverity::ERROR,
+            format!("`{c}` is never closed"),
+        )
+    })
+}
+
+/// Checks that `mainImage` is defined with the Shadertoy signature.
+fn check_main_image(tokens: &[(usize, Token)]) -> Option<ShaderProblem> {
+    let definition = tokens.windows(3).position(|window| {
+        matches!(
+            window,
+            [
+                (_, Token::Ident("void")),
+                (_, Token::Ident("mainImage")),
+                (_, Token::Punct('('))
+            ]
+        )
+    });
+    let Some(idx) = definition else {
+        return Some(problem(
+            0,
+            0,
+            DiagnosticSeverity::ERROR,
+            format!("the shader does not define `{MAIN_IMAGE}`"),
+        ));
+    };
+    let mut params: Vec<Vec<&str>> = vec![Vec::new()];
+    let mut depth = 0;
+    for &(_, token) in &tokens[idx + 3..] {
+        match token {
+            Token::Punct('(') => depth += 1,
+            Token::Punct(')') if depth == 0 => break,
+            Token::Punct(')') => depth -= 1,
+            Token::Punct(',') if depth == 0 => params.push(Vec::new()),
+            Token::Ident(ident) => params.last_mut()?.push(ident),
+            Token::Punct(_) => {}
+        }
+    }
+    let is_output = |param: &[&str]| param.contains(&"out") || param.contains(&"inout");
+    let valid = match params.as_slice() {
+        [color, coord] => {
+            color.contains(&"vec4")
+                && is_output(color)
+                && coord.contains(&"vec2")
+                && !is_output(coord)
+        }
+        _ => false,
+    };
+    if valid {
+        return None;
+    }
+    let (offset, _) = tokens[idx + 1];
+    Some(problem(
+        offset,
+        "mainImage".len(),
+        DiagnosticSeverity::ERROR,
+        format!("`mainImage` must have the signature `{MAIN_IMAGE}`"),
+    ))
+}
+
+/// Reports uniforms the shader declares itself, which Ghostty never sets or
+/// already declares, and Shadertoy-style uniforms Ghostty does not provide.
+fn check_uniforms(tokens: &[(usize, Token)]) -> Vec<ShaderProblem> {
+    let mut problems = Vec::new();
+    let mut declared: Vec<&str> = Vec::new();
+    let mut uniforms: Vec<(usize, &str)> = Vec::new();
+    let mut in_uniform = false;
+    for (idx, &(offset, token)) in tokens.iter().enumerate() {
+        let previous = idx.checked_sub(1).map(|idx| tokens[idx].1);
+        match token {
+            Token::Ident("uniform") => in_uniform = true,
+            Token::Punct(';') => in_uniform = false,
+            Token::Ident(ident) => {
+                let is_declaration = match previous {
+                    Some(Token::Ident(previous)) => {
+                        TYPES.contains(&previous) || previous == "define" || previous == "struct"
+                    }
+                    _ => false,
+                };
+                if is_declaration {
+                    declared.push(ident);
+                    if in_uniform {
+                        uniforms.push((offset, ident));
+                    }
+                }
+            }
+            Token::Punct(_) => {}
+        }
+    }
+
+    for (offset, name) in &uniforms {
+        let message = if PROVIDED_UNIFORMS.contains(name) {
+            format!("`{name}` is already declared by Ghostty")
+        } else {
+            format!("Ghostty does not set the uniform `{name}`, so it is always zero")
+        };
+        let severity = if PROVIDED_UNIFORMS.contains(name) {
+            DiagnosticSeverity::ERROR
+        } else {
+            DiagnosticSeverity::WARNING
+        };
+        problems.push(problem(*offset, name.len(), severity, message));
+    }
+
+    let mut reported: Vec<&str> = Vec::new();
+    for &(offset, token) in tokens {
+        let Token::Ident(ident) = token else {
+            continue;
+        };
+        let is_shadertoy_style = ident.len() > 1
+            && ident.starts_with('i')
+            && ident[1..].starts_with(|c: char| c.is_ascii_uppercase());
+        if !is_shadertoy_style
+            || PROVIDED_UNIFORMS.contains(&ident)
+            || declared.contains(&ident)
+            || reported.contains(&ident)
+        {
+            continue;
+        }
+        reported.push(ident);
+        let message = if ident.starts_with("iChannel") && ident[8..].parse::<u8>().is_ok() {
+            format!(
+                "Ghostty only provides `iChannel0`, the terminal screen; `{ident}` is not bound"
+            )
+        } else {
+            format!("`{ident}` is not a uniform Ghostty provides")
+        };
+        problems.push(problem(
+            offset,
+            ident.len(),
+            DiagnosticSeverity::WARNING,
+            message,
+        ));
+    }
+    problems
+}
+
+/// The LSP position of the byte `offset` in `text`.
+fn position_at(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() as u32;
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    Position::new(line, utf16_len(&before[line_start..]))
+}
+
+/// Checks every `custom-shader` file and reports its problems on the
+/// configuration entry, pointing into the shader with related information.
+/// Shaders that cannot be read are left to the missing path check.
+pub fn shader_diagnostics(docs: &Documents, uri: &Url, config: &ConfigFile) -> Vec<Diagnostic> {
+    let base_dir = base_dir(uri);
+    let mut diagnostics = Vec::new();
+    for value in path_values(base_dir.as_deref(), config) {
+        if value.entry.key != "custom-shader" {
+            continue;
+        }
+        let Some(source) = docs.read(&value.path) else {
+            continue;
+        };
+        let Ok(shader_uri) = Url::from_file_path(&value.path) else {
+            continue;
+        };
+        for problem in check_shader(&source) {
+            let range = Range::new(
+                position_at(&source, problem.span.0),
+                position_at(&source, problem.span.1),
+            );
+            let mut diagnostic = diagnostic(
+                value.range,
+                problem.severity,
+                "invalid-custom-shader",
+                format!("custom shader: {}", problem.message),
+            );
+            diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+                location: Location::new(shader_uri.clone(), range),
+                message: problem.message,
+            }]);
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<((usize, usize), String)> {
+        check_shader(source)
+            .into_iter()
+            .map(|problem| (problem.span, problem.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_shader() {
+        let source = "// void mainImage(vec2 uv) { iChannel1 }\nuniform float uUnused2;\nvoid mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    vec2 uv = fragCoord / iResolution.xy;\n    fragColor = texture(iChannel0, uv) * sin(iTime);\n}\n";
+        assert_eq!(
+            messages(source),
+            vec![(
+                (55, 63),
+                "Ghostty does not set the uniform `uUnused2`, so it is always zero".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(strip_comments("a /*/ x */ b"), "a          b");
+        assert_eq!(strip_comments("a /**/ b // c\nd"), "a      b     \nd");
+    }
+
+    #[test]
+    fn test_main_image_signature() {
+        assert_eq!(
+            messages("void main() {}"),
+            vec![((0, 0), format!("the shader does not define `{MAIN_IMAGE}`"))]
+        );
+        assert_eq!(
+            messages("void mainImage(vec4 color, vec2 coord) {}"),
+            vec![(
+                (5, 14),
+                format!("`mainImage` must have the signature `{MAIN_IMAGE}`")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unprovided_uniforms() {
+        let source = "#define iSpeed 2.0\nuniform float iTime;\nvoid mainImage(out vec4 c, in vec2 p) {\n    c = texture(iChannel1, p) * iSpeed + iKeyboard;\n}";
+        let messages: Vec<_> = messages(source).into_iter().map(|(_, m)| m).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`iTime` is already declared by Ghostty".to_string(),
+                "Ghostty only provides `iChannel0`, the terminal screen; `iChannel1` is not bound"
+                    .to_string(),
+                "`iKeyboard` is not a uniform Ghostty provides".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_brackets() {
+        let source = "void mainImage(out vec4 c, in vec2 p) {\n    c = vec4(1.0;\n}";
+        assert_eq!(messages(source)[0], ((58, 59), "unmatched `}`".to_string()));
+    }
+
+    #[test]
+    fn test_diagnostics_point_into_shader() {
+        let dir = std::env::temp_dir().join(format!("ghostty-lsp-shader-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("crt.glsl"), "void main() {\n}\n").unwrap();
+        let uri = Url::from_file_path(dir.join("config")).unwrap();
+        let config = ConfigFile::parse("custom-shader = crt.glsl\n");
+        let diagnostics = shader_diagnostics(&Documents::default(), &uri, &config);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 16));
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(
+            related[0].location.uri,
+            Url::from_file_path(dir.join("crt.glsl")).unwrap()
+        );
+        assert_eq!(related[0].location.range.start, Position::new(0, 0));
+    }
+}
@@ -0,0 +1,208 @@
+//! Validation of `command` and the shell integration settings.
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, Url};
+
+use crate::diagnostics::diagnostic;
+use crate::links::base_dir;
+use crate::parser::{ConfigFile, Entry};
+use crate::paths;
+
+/// Shells Ghostty has an integration for.
+pub const INTEGRATED_SHELLS: [&str; 3] = ["bash", "fish", "zsh"];
+
+/// Features accepted by `shell-integration-features`, each of which may be
+/// prefixed with `no-` to disable it.
+pub const SHELL_INTEGRATION_FEATURES: [&str; 3] = ["cursor", "sudo", "title"];
+
+/// The program of a `command` value, without its arguments.
+pub fn command_program(value: &str) -> Option<&str> {
+    value.split_whitespace().next()
+}
+
+/// Resolves the program of a `command` the way Ghostty does: paths are used
+/// as-is (relative ones against `base_dir`) and bare names are looked up in
+/// `path_var`, the value of `PATH`.
+pub fn resolve_program(
+    program: &str,
+    base_dir: Option<&Path>,
+    path_var: Option<&OsStr>,
+) -> Option<PathBuf> {
+    if program.contains('/') || program.starts_with('~') {
+        let expanded = paths::expand_home(program);
+        return match base_dir {
+            Some(base_dir) => Some(paths::resolve(base_dir, program)),
+            None if expanded.is_absolute() => Some(expanded),
+            None => None,
+        };
+    }
+    std::env::split_paths(path_var?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// The name of the shell a program path launches, such as `zsh` for
+/// `/usr/local/bin/zsh`.
+fn shell_name(program: &str) -> &str {
+    let name = program.rsplit('/').next().unwrap_or(program);
+    // Login shells are sometimes written with a leading dash.
+    name.trim_start_matches('-')
+}
+
+/// Hover text for the resolved program of a `command` entry.
+pub fn command_hover(uri: &Url, entry: &Entry) -> Option<String> {
+    let program = command_program(&entry.value)?;
+    let path_var = std::env::var_os("PATH");
+    let resolved = resolve_program(program, base_dir(uri).as_deref(), path_var.as_deref())?;
+    Some(format!("Resolves to `{}`", resolved.display()))
+}
+
+pub fn shell_diagnostics(uri: &Url, config: &ConfigFile) -> Vec<Diagnostic> {
+    let path_var = std::env::var_os("PATH");
+    check_shell(base_dir(uri).as_deref(), path_var.as_deref(), config)
+}
+
+fn check_shell(
+    base_dir: Option<&Path>,
+    path_var: Option<&OsStr>,
+    config: &ConfigFile,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let last = |key: &str| config.entries().filter(|entry| entry.key == key).last();
+
+    let command = last("command").filter(|entry| !entry.value.is_empty());
+    let program = command.and_then(|entry| command_program(&entry.value));
+    if let (Some(entry), Some(program)) = (command, program) {
+        let range = entry.value_subrange(0, program.len());
+        match resolve_program(program, base_dir, path_var) {
+            // Missing paths are reported by the missing-path rule.
+            Some(path) if path.exists() && !is_executable(&path) => {
+                diagnostics.push(diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "invalid-command",
+                    format!("`{}` is not executable", path.display()),
+                ));
+            }
+            None if !program.contains('/') => diagnostics.push(diagnostic(
+                range,
+                DiagnosticSeverity::WARNING,
+                "invalid-command",
+                format!("`{program}` was not found in PATH"),
+            )),
+            _ => {}
+        }
+    }
+
+    if let (Some(integration), Some(program)) = (last("shell-integration"), program) {
+        let shell = shell_name(program);
+        let value = integration.value.as_str();
+        if INTEGRATED_SHELLS.contains(&value)
+            && INTEGRATED_SHELLS.contains(&shell)
+            && value != shell
+        {
+            diagnostics.push(diagnostic(
+                integration.value_range,
+                DiagnosticSeverity::WARNING,
+                "shell-integration-mismatch",
+                format!("shell-integration is `{value}` but command launches `{shell}`"),
+            ));
+        }
+    }
+
+    for entry in config
+        .entries()
+        .filter(|entry| entry.key == "shell-integration-features")
+    {
+        let mut offset = 0;
+        for feature in entry.value.split(',') {
+            let start = offset + feature.len() - feature.trim_start().len();
+            offset += feature.len() + 1;
+            let feature = feature.trim();
+            let name = feature.strip_prefix("no-").unwrap_or(feature);
+            if feature.is_empty() || SHELL_INTEGRATION_FEATURES.contains(&name) {
+                continue;
+            }
+            diagnostics.push(diagnostic(
+                entry.value_subrange(start, start + feature.len()),
+                DiagnosticSeverity::WARNING,
+                "unknown-shell-integration-feature",
+                format!(
+                    "unknown shell integration feature `{feature}`, expected one of {}",
+                    SHELL_INTEGRATION_FEATURES.join(", ")
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(text: &str) -> Vec<String> {
+        // `sh` exists on every system the tests run on.
+        let path_var = std::env::var_os("PATH");
+        check_shell(None, path_var.as_deref(), &ConfigFile::parse(text))
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_program() {
+        let path_var = std::env::var_os("PATH");
+        let sh = resolve_program("sh", None, path_var.as_deref()).unwrap();
+        assert!(sh.is_absolute());
+        assert_eq!(
+            resolve_program("bin/fish", Some(Path::new("/cfg")), None),
+            Some(PathBuf::from("/cfg/bin/fish"))
+        );
+        assert_eq!(
+            resolve_program("not-a-real-shell-xyz", None, path_var.as_deref()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_command_not_found() {
+        assert_eq!(
+            messages("command = not-a-real-shell-xyz --login\n"),
+            vec!["`not-a-real-shell-xyz` was not found in PATH"]
+        );
+        assert!(messages("command = sh -c true\n").is_empty());
+    }
+
+    #[test]
+    fn test_shell_integration_mismatch() {
+        assert_eq!(
+            messages("command = /bin/zsh\nshell-integration = fish\n"),
+            vec!["shell-integration is `fish` but command launches `zsh`"]
+        );
+        assert!(messages("command = /bin/zsh\nshell-integration = zsh\n").is_empty());
+        assert!(messages("command = /bin/zsh\nshell-integration = detect\n").is_empty());
+    }
+
+    #[test]
+    fn test_unknown_shell_integration_features() {
+        assert_eq!(
+            messages("shell-integration-features = no-cursor, sudo,titl\n"),
+            vec!["unknown shell integration feature `titl`, expected one of cursor, sudo, title"]
+        );
+    }
+}
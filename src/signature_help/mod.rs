@@ -0,0 +1,106 @@
+//! Signature help for values with a mini-grammar, such as `palette = N=COLOR`.
+use lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, Position, SignatureHelp,
+    SignatureInformation,
+};
+
+use crate::definitions::get_config_key;
+use crate::parser::{byte_offset, utf16_len, ConfigFile};
+
+/// Characters that should trigger signature help in the client.
+pub const TRIGGER_CHARACTERS: [&str; 3] = ["=", ":", ","];
+
+pub fn signature_help(config: &ConfigFile, position: Position) -> Option<SignatureHelp> {
+    let entry = config.entry_at_line(position.line)?;
+    let key = get_config_key(&entry.key)?;
+    let parameters = key.kind.parameters()?;
+
+    // The cursor must be past the `=` that separates the key from the value.
+    if position.character < entry.key_range.end.character {
+        return None;
+    }
+    let line = &config.lines[position.line as usize].text;
+    let line_before = &line[..byte_offset(line, position.character)];
+    let eq = line_before.find('=')?;
+    let value_before = line_before[eq + 1..].trim_start();
+    let value_before = value_before.strip_prefix('"').unwrap_or(value_before);
+    let active = key
+        .kind
+        .active_parameter(value_before, value_before.len())?;
+
+    let mut label = format!("{} = ", key.name);
+    let mut infos = Vec::new();
+    for (idx, parameter) in parameters.iter().enumerate() {
+        if idx > 0 {
+            label.push_str(key.kind.separators()[idx - 1]);
+        }
+        let start = utf16_len(&label);
+        label.push_str(parameter.name);
+        infos.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&label)]),
+            documentation: parameter.doc().map(Documentation::String),
+        });
+    }
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: None,
+            parameters: Some(infos),
+            active_parameter: Some(active),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(text: &str, character: u32) -> Option<(String, u32)> {
+        let config = ConfigFile::parse(text);
+        let help = signature_help(&config, Position::new(0, character))?;
+        let signature = &help.signatures[0];
+        Some((signature.label.clone(), signature.active_parameter.unwrap()))
+    }
+
+    #[test]
+    fn test_palette_signature() {
+        assert_eq!(
+            active("palette = 4=#0000ff", 11),
+            Some(("palette = N=COLOR".to_string(), 0))
+        );
+        assert_eq!(
+            active("palette = 4=#0000ff", 12),
+            Some(("palette = N=COLOR".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn test_keybind_signature() {
+        let text = "keybind = ctrl+a=csi:A";
+        assert_eq!(active(text, 12).unwrap().1, 0);
+        assert_eq!(active(text, 18).unwrap().1, 1);
+        assert_eq!(active(text, 21).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_codepoint_map_signature_and_offsets() {
+        let config = ConfigFile::parse("font-codepoint-map = U+E000,U+E001=Symbols");
+        let help = signature_help(&config, Position::new(0, 28)).unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(signature.active_parameter, Some(0));
+        let parameters = signature.parameters.as_ref().unwrap();
+        assert!(matches!(
+            parameters[1].label,
+            ParameterLabel::LabelOffsets([35, 43])
+        ));
+    }
+
+    #[test]
+    fn test_no_signature_for_plain_values_or_key() {
+        assert_eq!(active("font-size = 12", 13), None);
+        assert_eq!(active("palette = 4=#000000", 3), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! State shared by the request and notification handlers.
+use std::path::PathBuf;
+
+use crossbeam_channel::Sender;
+use lsp_types::TraceValue;
+
+use crate::capabilities::ClientSupport;
+use crate::definitions::{detect_version, Version, LATEST};
+use crate::documents::Documents;
+use crate::fonts::{font_dirs, FontCache, FontCaches};
+use crate::settings::Settings;
+
+/// Work done on a background thread that the main loop has to act on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    /// The font index is ready, so the font rules can be checked.
+    FontsIndexed,
+    /// `ghostty --version` finished, with the version of the `ghostty` on
+    /// `PATH` if there is one.
+    VersionDetected(Option<Version>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ServerState {
+    pub docs: Documents,
+    pub fonts: FontCache,
+    /// The font caches shared with the other clients of the server.
+    pub font_caches: FontCaches,
+    pub settings: Settings,
+    pub client: ClientSupport,
+    /// The workspace folders, searched for files by workspace diagnostics.
+    pub roots: Vec<PathBuf>,
+    /// The version of the `ghostty` on `PATH`, if there is one.
+    pub detected_version: Option<Version>,
+    /// Where background threads report back to the main loop.
+    pub background: Option<Sender<Background>>,
+    /// The `$/logTrace` verbosity the client asked for.
+    pub trace: TraceValue,
+}
+
+impl ServerState {
+    /// Starts indexing the fonts in the system and configured font
+    /// directories, unless another client of the server already has in
+    /// `font_caches`, and, unless the settings name a version, detecting the
+    /// installed Ghostty. Both report to `background` once done.
+    pub fn new(
+        client: ClientSupport,
+        settings: Settings,
+        background: Sender<Background>,
+        font_caches: FontCaches,
+    ) -> Self {
+        let mut state = Self {
+            client,
+            background: Some(background),
+            font_caches,
+            ..Default::default()
+        };
+        state.fonts = state.index_fonts(&settings);
+        if settings.ghostty_version.is_none() {
+            state.detect_version();
+        }
+        state.settings = settings;
+        state
+    }
+
+    /// The Ghostty version the schema is checked against: the one from the
+    /// settings, then the installed one, then the latest.
+    pub fn ghostty_version(&self) -> Version {
+        self.settings
+            .ghostty_version
+            .or(self.detected_version)
+            .unwrap_or(LATEST)
+    }
+
+    /// Replaces the settings, indexing the fonts again if the font
+    /// directories changed and detecting the installed Ghostty if the
+    /// version is no longer set and was never detected.
+    pub fn apply_settings(&mut self, settings: Settings) {
+        if settings.font_dirs != self.settings.font_dirs {
+            self.fonts = self.index_fonts(&settings);
+        }
+        if settings.ghostty_version.is_none()
+            && self.settings.ghostty_version.is_some()
+            && self.detected_version.is_none()
+        {
+            self.detect_version();
+        }
+        self.settings = settings;
+    }
+
+    /// Runs `ghostty --version` on a thread of its own, as it can take a
+    /// while and the version is only a fallback for the settings.
+    fn detect_version(&self) {
+        let Some(background) = self.background.clone() else {
+            return;
+        };
+        std::thread::spawn(move || {
+            let _ = background.send(Background::VersionDetected(detect_version()));
+        });
+    }
+
+    fn index_fonts(&self, settings: &Settings) -> FontCache {
+        let background = self.background.clone();
+        self.font_caches
+            .get(settings.font_dirs(font_dirs()), move || {
+                if let Some(background) = background {
+                    let _ = background.send(Background::FontsIndexed);
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Whether `new` reports a detected version within a short while.
+    fn detects_version(settings: Settings) -> bool {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let state = ServerState::new(
+            ClientSupport::default(),
+            settings,
+            sender,
+            FontCaches::default(),
+        );
+        assert_eq!(state.detected_version, None);
+        while let Ok(event) = receiver.recv_timeout(Duration::from_millis(500)) {
+            if matches!(event, Background::VersionDetected(_)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_version_is_only_detected_without_a_setting() {
+        let settings = Settings {
+            ghostty_version: Some(Version::new(1, 1, 0)),
+            ..Default::default()
+        };
+        assert!(!detects_version(settings));
+        assert!(detects_version(Settings::default()));
+    }
+}
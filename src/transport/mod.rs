@@ -0,0 +1,234 @@
+//! The channels the language server talks to its client over.
+//!
+//! Stdio and TCP use the transports built into `lsp_server`. Unix domain
+//! sockets are not among them, so those get reader and writer threads of
+//! their own that speak the same framing. Stdio and TCP serve one client,
+//! while a socket keeps accepting them, so several editors can share one
+//! server.
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use lsp_server::{Connection, Message};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Transport {
+    #[default]
+    Stdio,
+    /// Waits for a client to connect to this TCP address.
+    Listen(String),
+    /// Connects to a client listening on this TCP address.
+    Connect(String),
+    /// Serves every client that connects to a Unix domain socket at this
+    /// path.
+    Socket(PathBuf),
+}
+
+/// The threads moving messages between a [`Connection`] and its transport.
+pub enum IoThreads {
+    Lsp(lsp_server::IoThreads),
+    Stream {
+        reader: JoinHandle<io::Result<()>>,
+        writer: JoinHandle<io::Result<()>>,
+    },
+}
+
+impl IoThreads {
+    /// Waits for the transport to shut down.
+    pub fn join(self) -> io::Result<()> {
+        match self {
+            Self::Lsp(threads) => threads.join(),
+            Self::Stream { reader, writer } => {
+                for thread in [reader, writer] {
+                    match thread.join() {
+                        Ok(result) => result?,
+                        Err(_) => return Err(io::Error::other("transport thread panicked")),
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Opens a transport that serves one client, blocking until it is
+/// connected. Sockets serve every client that connects through a
+/// [`SocketListener`] instead.
+pub fn open(transport: &Transport) -> io::Result<(Connection, IoThreads)> {
+    let (connection, threads) = match transport {
+        Transport::Stdio => Connection::stdio(),
+        Transport::Listen(addr) => Connection::listen(addr.as_str())?,
+        Transport::Connect(addr) => Connection::connect(addr.as_str())?,
+        Transport::Socket(path) => return SocketListener::bind(path)?.accept(),
+    };
+    Ok((connection, IoThreads::Lsp(threads)))
+}
+
+/// A Unix domain socket that clients connect to. The socket file is removed
+/// when the listener is dropped.
+pub struct SocketListener {
+    path: PathBuf,
+    #[cfg(unix)]
+    listener: std::os::unix::net::UnixListener,
+}
+
+#[cfg(unix)]
+impl SocketListener {
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        // A socket left behind by a server that did not shut down cleanly
+        // makes binding fail. Nothing accepts connections on it, unlike the
+        // socket of a server that is still running.
+        let listener = match UnixListener::bind(path) {
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse => match UnixStream::connect(path) {
+                Ok(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("a server is already listening on `{}`", path.display()),
+                    ));
+                }
+                Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                    std::fs::remove_file(path)?;
+                    UnixListener::bind(path)?
+                }
+                Err(err) => return Err(err),
+            },
+            listener => listener?,
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener,
+        })
+    }
+
+    /// Blocks until the next client connects.
+    pub fn accept(&self) -> io::Result<(Connection, IoThreads)> {
+        let (stream, _) = self.listener.accept()?;
+        let reader = stream.try_clone()?;
+        Ok(stream_transport(reader, stream))
+    }
+}
+
+#[cfg(not(unix))]
+impl SocketListener {
+    pub fn bind(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Unix domain sockets are not supported on this platform",
+        ))
+    }
+
+    pub fn accept(&self) -> io::Result<(Connection, IoThreads)> {
+        unreachable!("sockets cannot be bound on this platform")
+    }
+}
+
+impl Drop for SocketListener {
+    fn drop(&mut self) {
+        // The next server can take the socket's place.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// A connection reading messages from `reader` and writing them to `writer`,
+/// until the client sends `exit` or closes the stream.
+fn stream_transport(
+    reader: impl io::Read + Send + 'static,
+    mut writer: impl io::Write + Send + 'static,
+) -> (Connection, IoThreads) {
+    let (reader_sender, receiver) = crossbeam_channel::bounded::<Message>(0);
+    let (sender, writer_receiver) = crossbeam_channel::bounded::<Message>(0);
+    let reader = std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        while let Some(msg) = Message::read(&mut reader)? {
+            let is_exit = matches!(&msg, Message::Notification(notif) if notif.method == "exit");
+            if reader_sender.send(msg).is_err() || is_exit {
+                break;
+            }
+        }
+        Ok(())
+    });
+    let writer = std::thread::spawn(move || {
+        writer_receiver
+            .into_iter()
+            .try_for_each(|msg| msg.write(&mut writer))
+    });
+    (
+        Connection { sender, receiver },
+        IoThreads::Stream { reader, writer },
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use lsp_server::Notification;
+    use std::io::{BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_socket_transport() {
+        let path = std::env::temp_dir().join(format!("ghostty-lsp-{}.sock", std::process::id()));
+        let server_path = path.clone();
+        let server = std::thread::spawn(move || open(&Transport::Socket(server_path)).unwrap());
+        let mut client = loop {
+            match UnixStream::connect(&path) {
+                Ok(client) => break client,
+                Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        };
+        let (connection, threads) = server.join().unwrap();
+
+        let hello = Notification::new("hello".to_string(), serde_json::json!({ "n": 1 }));
+        Message::Notification(hello).write(&mut client).unwrap();
+        match connection.receiver.recv().unwrap() {
+            Message::Notification(notif) => assert_eq!(notif.method, "hello"),
+            msg => panic!("unexpected message {msg:?}"),
+        }
+
+        let reply = Notification::new("reply".to_string(), serde_json::Value::Null);
+        connection
+            .sender
+            .send(Message::Notification(reply))
+            .unwrap();
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        match Message::read(&mut reader).unwrap() {
+            Some(Message::Notification(notif)) => assert_eq!(notif.method, "reply"),
+            msg => panic!("unexpected message {msg:?}"),
+        }
+
+        let exit = Notification::new("exit".to_string(), serde_json::Value::Null);
+        Message::Notification(exit).write(&mut client).unwrap();
+        client.flush().unwrap();
+        connection.receiver.recv().unwrap();
+        drop(connection);
+        threads.join().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_socket_in_use() {
+        use std::os::unix::net::UnixListener;
+
+        let path =
+            std::env::temp_dir().join(format!("ghostty-lsp-used-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let err = open(&Transport::Socket(path.clone())).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        assert!(path.exists());
+
+        // Once the server is gone, its socket is stale and replaced.
+        drop(listener);
+        let server_path = path.clone();
+        let server = std::thread::spawn(move || open(&Transport::Socket(server_path)).is_ok());
+        loop {
+            if UnixStream::connect(&path).is_ok() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(server.join().unwrap());
+    }
+}
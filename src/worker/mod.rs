@@ -0,0 +1,237 @@
+//! Running analysis off the main loop.
+//!
+//! The main loop owns the server state and only applies notifications to
+//! it. Requests and diagnostics run on a [`WorkerPool`] against a snapshot
+//! of the state, so a slow request does not hold up the ones behind it.
+//! [`InFlight`] tracks which requests still need a response so that
+//! `$/cancelRequest` can answer them early, and [`Debouncer`] delays the
+//! diagnostics of a document until it stops changing.
+use std::collections::{HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use lsp_server::RequestId;
+use lsp_types::Url;
+
+/// How long a document has to stay unchanged before its diagnostics are
+/// computed.
+pub const DIAGNOSTICS_DELAY: Duration = Duration::from_millis(200);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed number of threads running jobs in the order they were spawned.
+pub struct WorkerPool {
+    sender: Option<Sender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let threads = (0..size.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                std::thread::spawn(move || loop {
+                    // The lock is released before the job runs so the other
+                    // threads can pick up work meanwhile.
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    let Ok(job) = job else {
+                        return;
+                    };
+                    if catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        log::error!("A worker job panicked");
+                    }
+                })
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            threads,
+        }
+    }
+
+    /// A pool with a thread per CPU, leaving one for the main loop.
+    pub fn with_available_parallelism() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(1, |cpus| cpus.get());
+        Self::new(cpus.saturating_sub(1))
+    }
+
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        if let Some(sender) = &self.sender {
+            // The threads only stop once the sender is dropped.
+            let _ = sender.send(Box::new(job));
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    /// Waits for the jobs already spawned to finish.
+    fn drop(&mut self) {
+        self.sender.take();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The requests that have not been answered yet. Whoever calls
+/// [`InFlight::finish`] first for a request gets to answer it: the worker
+/// with its result or the main loop with a cancellation.
+#[derive(Debug, Clone, Default)]
+pub struct InFlight {
+    ids: Arc<Mutex<HashSet<RequestId>>>,
+}
+
+impl InFlight {
+    pub fn start(&self, id: RequestId) {
+        if let Ok(mut ids) = self.ids.lock() {
+            ids.insert(id);
+        }
+    }
+
+    /// Marks the request as answered. Returns `false` when it already was,
+    /// in which case the caller must not respond.
+    pub fn finish(&self, id: &RequestId) -> bool {
+        self.ids.lock().is_ok_and(|mut ids| ids.remove(id))
+    }
+}
+
+/// The documents waiting for their diagnostics, each with the time they
+/// are due.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    due: HashMap<Url, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            due: HashMap::new(),
+        }
+    }
+
+    /// Schedules the diagnostics of `uri`, pushing them back if they are
+    /// already scheduled.
+    pub fn schedule(&mut self, uri: Url) {
+        self.due.insert(uri, Instant::now() + self.delay);
+    }
+
+    pub fn cancel(&mut self, uri: &Url) {
+        self.due.remove(uri);
+    }
+
+    /// How long until the next document is due, or `None` when none is
+    /// waiting.
+    pub fn timeout(&self) -> Option<Duration> {
+        let next = self.due.values().min()?;
+        Some(next.saturating_duration_since(Instant::now()))
+    }
+
+    /// Removes and returns the documents that are due.
+    pub fn take_due(&mut self) -> Vec<Url> {
+        let now = Instant::now();
+        let due: Vec<Url> = self
+            .due
+            .iter()
+            .filter(|(_, due)| **due <= now)
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        for uri in &due {
+            self.due.remove(uri);
+        }
+        due
+    }
+}
+
+/// A counter per document, bumped whenever its diagnostics are computed, so
+/// that a slow job does not publish over the results of a newer one.
+#[derive(Debug, Clone, Default)]
+pub struct Generations {
+    generations: Arc<Mutex<HashMap<Url, u64>>>,
+}
+
+impl Generations {
+    /// Starts a new generation for `uri` and returns it.
+    pub fn next(&self, uri: &Url) -> u64 {
+        let Ok(mut generations) = self.generations.lock() else {
+            return 0;
+        };
+        let generation = generations.entry(uri.clone()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    pub fn is_current(&self, uri: &Url, generation: u64) -> bool {
+        self.generations
+            .lock()
+            .is_ok_and(|generations| generations.get(uri) == Some(&generation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_runs_jobs_and_survives_panics() {
+        let (sender, receiver) = mpsc::channel();
+        let pool = WorkerPool::new(2);
+        pool.spawn(|| panic!("boom"));
+        for n in 0..4 {
+            let sender = sender.clone();
+            pool.spawn(move || sender.send(n).unwrap());
+        }
+        drop(pool);
+        let mut results: Vec<i32> = receiver.try_iter().collect();
+        results.sort();
+        assert_eq!(results, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_in_flight_answers_once() {
+        let in_flight = InFlight::default();
+        let id = RequestId::from(7);
+        in_flight.start(id.clone());
+        assert!(in_flight.finish(&id));
+        assert!(!in_flight.finish(&id));
+        assert!(!in_flight.finish(&RequestId::from(8)));
+    }
+
+    #[test]
+    fn test_debouncer() {
+        let uri = Url::parse("file:///config").unwrap();
+        let mut debouncer = Debouncer::new(Duration::from_secs(60));
+        assert_eq!(debouncer.timeout(), None);
+        debouncer.schedule(uri.clone());
+        assert!(debouncer.timeout().unwrap() > Duration::from_secs(59));
+        assert!(debouncer.take_due().is_empty());
+
+        let mut debouncer = Debouncer::new(Duration::ZERO);
+        debouncer.schedule(uri.clone());
+        assert_eq!(debouncer.take_due(), vec![uri.clone()]);
+        assert!(debouncer.take_due().is_empty());
+        debouncer.schedule(uri.clone());
+        debouncer.cancel(&uri);
+        assert_eq!(debouncer.timeout(), None);
+    }
+
+    #[test]
+    fn test_generations() {
+        let uri = Url::parse("file:///config").unwrap();
+        let generations = Generations::default();
+        let first = generations.next(&uri);
+        assert!(generations.is_current(&uri, first));
+        let second = generations.next(&uri);
+        assert!(!generations.is_current(&uri, first));
+        assert!(generations.is_current(&uri, second));
+    }
+}
@@ -0,0 +1,282 @@
+//! Validation and completion of `working-directory`.
+//!
+//! The value is a directory, or `home` or `inherit`. `~` and environment
+//! variables are expanded and relative paths are relative to the file that
+//! contains the entry.
+use std::path::{Path, PathBuf};
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Diagnostic, DiagnosticSeverity,
+    TextEdit, Url,
+};
+
+use crate::definitions::get_config_key;
+use crate::diagnostics::diagnostic;
+use crate::links::base_dir;
+use crate::parser::{ConfigFile, Entry};
+use crate::paths;
+
+/// Values of `working-directory` that are not paths.
+pub const SPECIAL_VALUES: [(&str, &str); 2] = [
+    ("home", "the home directory of the user"),
+    (
+        "inherit",
+        "the working directory of the process that launched Ghostty",
+    ),
+];
+
+fn is_special(value: &str) -> bool {
+    SPECIAL_VALUES.iter().any(|(name, _)| *name == value)
+}
+
+/// Resolves a `working-directory` path, expanding environment variables and
+/// `~`. Relative paths need a `base_dir`.
+pub fn resolve(base_dir: Option<&Path>, value: &str) -> Option<PathBuf> {
+    let (expanded, _) = paths::expand_env(value);
+    let path = paths::expand_home(&expanded);
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        base_dir.map(|base_dir| base_dir.join(path))
+    }
+}
+
+pub fn working_directory_diagnostics(uri: &Url, config: &ConfigFile) -> Vec<Diagnostic> {
+    check_working_directory(base_dir(uri).as_deref(), config)
+}
+
+fn check_working_directory(base_dir: Option<&Path>, config: &ConfigFile) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let entries: Vec<&Entry> = config
+        .entries()
+        .filter(|entry| entry.key == "working-directory" && !entry.value.is_empty())
+        .collect();
+    for entry in &entries {
+        if is_special(&entry.value) {
+            continue;
+        }
+        let (_, missing) = paths::expand_env(&entry.value);
+        if !missing.is_empty() {
+            for name in missing {
+                diagnostics.push(diagnostic(
+                    entry.value_range,
+                    DiagnosticSeverity::WARNING,
+                    "invalid-working-directory",
+                    format!("environment variable `{name}` is not set"),
+                ));
+            }
+            continue;
+        }
+        let Some(path) = resolve(base_dir, &entry.value) else {
+            continue;
+        };
+        let problem = if !path.exists() {
+            Some((DiagnosticSeverity::WARNING, "does not exist"))
+        } else if !path.is_dir() {
+            Some((DiagnosticSeverity::ERROR, "is not a directory"))
+        } else {
+            None
+        };
+        if let Some((severity, problem)) = problem {
+            diagnostics.push(diagnostic(
+                entry.value_range,
+                severity,
+                "invalid-working-directory",
+                format!("`{}` {problem}", path.display()),
+            ));
+        }
+    }
+
+    // Only the last entry takes effect, and with inheritance enabled it is
+    // only used by the first window.
+    let Some(effective) = entries.last() else {
+        return diagnostics;
+    };
+    if effective.value == "inherit" {
+        return diagnostics;
+    }
+    let inherit = config
+        .entries()
+        .filter(|entry| entry.key == "window-inherit-working-directory")
+        .last();
+    let default = get_config_key("window-inherit-working-directory").and_then(|key| key.default);
+    let inherits = match inherit {
+        Some(entry) if !entry.value.is_empty() => entry.value == "true",
+        _ => default == Some("true"),
+    };
+    if inherits {
+        let reason = match inherit {
+            Some(entry) => format!("is true on line {}", entry.line + 1),
+            None => "defaults to true".to_string(),
+        };
+        diagnostics.push(diagnostic(
+            effective.value_range,
+            DiagnosticSeverity::HINT,
+            "ineffective-working-directory",
+            format!(
+                "only the first window starts in this directory; new windows and tabs inherit the directory of the focused window because `window-inherit-working-directory` {reason}"
+            ),
+        ));
+    }
+    diagnostics
+}
+
+/// Completes the directory names that continue the partial path before the
+/// byte offset `offset` of the entry's value. The items replace the last
+/// path component.
+pub fn directory_completions(
+    base_dir: Option<&Path>,
+    entry: &Entry,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let partial = &entry.value[..offset];
+    let (dir, prefix) = match partial.rfind('/') {
+        Some(idx) => (&partial[..=idx], &partial[idx + 1..]),
+        None => ("", partial),
+    };
+    let range = entry.value_subrange(offset - prefix.len(), offset);
+    let edit = |label: String| CompletionTextEdit::Edit(TextEdit::new(range, label));
+
+    let mut items = Vec::new();
+    if dir.is_empty() {
+        for (name, detail) in SPECIAL_VALUES {
+            if name.starts_with(prefix) {
+                items.push(CompletionItem {
+                    label: name.to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some(detail.to_string()),
+                    text_edit: Some(edit(name.to_string())),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    let listed = if dir.is_empty() {
+        // A bare `~` has to be followed by a slash before it is a path.
+        if prefix.starts_with('~') {
+            return items;
+        }
+        base_dir.map(Path::to_path_buf)
+    } else {
+        resolve(base_dir, dir)
+    };
+    let Some(Ok(read_dir)) = listed.map(std::fs::read_dir) else {
+        return items;
+    };
+    let mut names: Vec<String> = read_dir
+        .flatten()
+        .filter(|child| child.path().is_dir())
+        .map(|child| child.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(prefix))
+        .filter(|name| !name.starts_with('.') || prefix.starts_with('.'))
+        .collect();
+    names.sort();
+    for name in names {
+        let label = format!("{name}/");
+        items.push(CompletionItem {
+            label: label.clone(),
+            kind: Some(CompletionItemKind::FOLDER),
+            text_edit: Some(edit(label)),
+            ..Default::default()
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(base_dir: Option<&Path>, text: &str) -> Vec<(String, String)> {
+        check_working_directory(base_dir, &ConfigFile::parse(text))
+            .into_iter()
+            .map(|diagnostic| {
+                let code = match diagnostic.code {
+                    Some(lsp_types::NumberOrString::String(code)) => code,
+                    _ => String::new(),
+                };
+                (code, diagnostic.message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_working_directory_paths() {
+        let file = std::env::temp_dir().join(format!("ghostty-lsp-wd-file-{}", std::process::id()));
+        std::fs::write(&file, "").unwrap();
+        let diagnostics = messages(
+            Some(Path::new("/")),
+            &format!("working-directory = $GHOSTTY_LSP_UNSET_VAR/src\nworking-directory = /nonexistent\nworking-directory = {}\nworking-directory = home\nwindow-inherit-working-directory = false\n", file.display()),
+        );
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            diagnostics,
+            vec![
+                (
+                    "invalid-working-directory".to_string(),
+                    "environment variable `GHOSTTY_LSP_UNSET_VAR` is not set".to_string()
+                ),
+                (
+                    "invalid-working-directory".to_string(),
+                    "`/nonexistent` does not exist".to_string()
+                ),
+                (
+                    "invalid-working-directory".to_string(),
+                    format!("`{}` is not a directory", file.display())
+                ),
+            ]
+        );
+        assert!(messages(None, "working-directory = ~\nworking-directory = $HOME\nwindow-inherit-working-directory = false").is_empty());
+    }
+
+    #[test]
+    fn test_inherited_working_directory_hint() {
+        let hint = messages(None, "working-directory = home\n");
+        assert_eq!(hint.len(), 1);
+        assert_eq!(hint[0].0, "ineffective-working-directory");
+        assert!(hint[0].1.ends_with("defaults to true"));
+        let hint = messages(
+            None,
+            "working-directory = home\nwindow-inherit-working-directory = true\n",
+        );
+        assert!(hint[0].1.ends_with("is true on line 2"));
+        assert!(messages(None, "working-directory = inherit\n").is_empty());
+    }
+
+    #[test]
+    fn test_directory_completions() {
+        let root = std::env::temp_dir().join(format!("ghostty-lsp-wd-{}", std::process::id()));
+        for dir in ["projects", "pictures", ".hidden", "other"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("plain-file"), "").unwrap();
+
+        let labels = |text: &str| {
+            let config = ConfigFile::parse(text);
+            let entry = config.entry_at_line(0).unwrap();
+            directory_completions(Some(&root), entry, entry.value.len())
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            labels("working-directory = p"),
+            vec!["pictures/", "projects/"]
+        );
+        assert_eq!(labels("working-directory = h"), vec!["home"]);
+        assert_eq!(labels("working-directory = ./."), vec![".hidden/"]);
+        let absolute = format!("working-directory = {}/o", root.display());
+        assert_eq!(labels(&absolute), vec!["other/"]);
+
+        let config = ConfigFile::parse("working-directory = ./pro");
+        let entry = config.entry_at_line(0).unwrap();
+        let items = directory_completions(Some(&root), entry, entry.value.len());
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(edit.range.start.character, 22);
+        assert_eq!(edit.range.end.character, 25);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
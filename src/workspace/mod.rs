@@ -0,0 +1,166 @@
+//! The Ghostty files of the workspace.
+//!
+//! Workspace diagnostics cover every configuration and theme file under the
+//! workspace roots, open or not, and the files those configurations include
+//! even when they live elsewhere. The walk is bounded so a workspace rooted
+//! at a home directory does not stall the server.
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{InitializeParams, Url};
+
+use crate::documents::Documents;
+use crate::includes::collect_includes;
+use crate::parser::ConfigFile;
+
+/// How many directories deep the walk goes below a root.
+const MAX_DEPTH: usize = 6;
+/// How many files the walk collects at most.
+const MAX_FILES: usize = 500;
+/// Directories that never hold Ghostty files but can be huge.
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// The workspace folders of the client, or its root when it does not
+/// support folders.
+pub fn roots(params: &InitializeParams) -> Vec<PathBuf> {
+    let uris: Vec<&Url> = match &params.workspace_folders {
+        Some(folders) => folders.iter().map(|folder| &folder.uri).collect(),
+        #[allow(deprecated)]
+        None => params.root_uri.iter().collect(),
+    };
+    uris.into_iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect()
+}
+
+/// A Ghostty configuration: `config` or `config.ghostty` in a `ghostty`
+/// directory, or any file with the `.ghostty` extension.
+pub fn is_config_file(path: &Path) -> bool {
+    let in_ghostty_dir = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == "ghostty");
+    let name = path.file_name().and_then(|name| name.to_str());
+    path.extension().is_some_and(|ext| ext == "ghostty")
+        || (in_ghostty_dir && name == Some("config"))
+}
+
+/// A theme: a file in the `themes` directory next to a configuration.
+pub fn is_theme_file(path: &Path) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    dir.file_name().is_some_and(|name| name == "themes")
+        && dir
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|name| name == "ghostty")
+}
+
+/// The configuration and theme files under `roots` followed by the files
+/// their configurations include, without duplicates.
+pub fn ghostty_files(roots: &[PathBuf], docs: &Documents) -> Vec<PathBuf> {
+    // Paths are canonical so that an include of a file that was already
+    // found is recognized.
+    let mut files = Vec::new();
+    for root in roots {
+        if let Ok(root) = root.canonicalize() {
+            walk(&root, 0, &mut files);
+        }
+    }
+    let mut seen: HashSet<PathBuf> = files.iter().cloned().collect();
+    let configs: Vec<PathBuf> = files
+        .iter()
+        .filter(|path| is_config_file(path))
+        .cloned()
+        .collect();
+    for path in configs {
+        let Some(text) = docs.read(&path) else {
+            continue;
+        };
+        for included in collect_includes(docs, &path, &ConfigFile::parse(&text)) {
+            let Ok(included) = included.path.canonicalize() else {
+                continue;
+            };
+            if files.len() < MAX_FILES && seen.insert(included.clone()) {
+                files.push(included);
+            }
+        }
+    }
+    files
+}
+
+fn walk(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        if files.len() >= MAX_FILES {
+            log::debug!("Stopping the workspace walk at {MAX_FILES} files");
+            return;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if file_type.is_dir() {
+            // Ghostty's own directory is `.config/ghostty`, so hidden
+            // directories are only skipped below it.
+            let hidden = name.starts_with('.') && name != ".config";
+            if depth < MAX_DEPTH && !hidden && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                walk(&path, depth + 1, files);
+            }
+        } else if is_config_file(&path) || is_theme_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kinds() {
+        assert!(is_config_file(Path::new("/home/me/.config/ghostty/config")));
+        assert!(is_config_file(Path::new("/dotfiles/work.ghostty")));
+        assert!(!is_config_file(Path::new("/dotfiles/git/config")));
+        assert!(is_theme_file(Path::new("/dotfiles/ghostty/themes/Dark")));
+        assert!(!is_theme_file(Path::new("/dotfiles/themes/Dark")));
+    }
+
+    #[test]
+    fn test_ghostty_files() {
+        let root = std::env::temp_dir().join(format!("ghostty-lsp-ws-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        let ghostty = root.join("dotfiles/ghostty");
+        fs::create_dir_all(ghostty.join("themes")).unwrap();
+        fs::create_dir_all(root.join(".git/ghostty")).unwrap();
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::write(ghostty.join("config"), "config-file = ../../shared/keys\n").unwrap();
+        fs::write(ghostty.join("themes/Dark"), "background = #000000\n").unwrap();
+        fs::write(root.join(".git/ghostty/config"), "").unwrap();
+        fs::write(
+            root.join("shared/keys"),
+            "keybind = ctrl+a=copy_to_clipboard\n",
+        )
+        .unwrap();
+
+        let files = ghostty_files(std::slice::from_ref(&root), &Documents::default());
+        assert_eq!(
+            files,
+            vec![
+                ghostty.join("config"),
+                ghostty.join("themes/Dark"),
+                root.join("shared/keys"),
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
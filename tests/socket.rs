@@ -0,0 +1,134 @@
+//! Runs the server on a Unix domain socket and talks to it as editors do.
+#![cfg(unix)]
+
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use lsp_server::{Message, Notification, Request, RequestId};
+use serde_json::{json, Value};
+
+/// The server process and its socket, stopped and removed when the test
+/// ends.
+struct Server(Child, PathBuf);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+        let _ = std::fs::remove_file(&self.1);
+    }
+}
+
+struct Client {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+}
+
+impl Client {
+    fn connect(path: &Path) -> Self {
+        let stream = (0..500)
+            .find_map(|_| {
+                UnixStream::connect(path)
+                    .inspect_err(|_| std::thread::sleep(Duration::from_millis(10)))
+                    .ok()
+            })
+            .expect("the server does not listen");
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        Self { stream, reader }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let notif = Notification::new(method.to_string(), params);
+        Message::Notification(notif)
+            .write(&mut self.stream)
+            .unwrap();
+    }
+
+    /// Sends a request and returns its result, skipping the notifications
+    /// and requests the server sends in the meantime.
+    fn request(&mut self, id: i32, method: &str, params: Value) -> Value {
+        let req = Request::new(RequestId::from(id), method.to_string(), params);
+        Message::Request(req).write(&mut self.stream).unwrap();
+        loop {
+            match Message::read(&mut self.reader).unwrap() {
+                Some(Message::Response(resp)) if resp.id == RequestId::from(id) => {
+                    if let Some(err) = resp.error {
+                        panic!("{method} failed: {}", err.message);
+                    }
+                    return resp.result.unwrap_or_default();
+                }
+                Some(_) => {}
+                None => panic!("the server closed the connection"),
+            }
+        }
+    }
+
+    /// Runs initialize, hover and shutdown, returning the hover contents.
+    fn session(&mut self, uri: &str) -> String {
+        let capabilities = json!({
+            "textDocument": { "hover": { "contentFormat": ["plaintext"] } }
+        });
+        let init = self.request(
+            1,
+            "initialize",
+            json!({
+                "capabilities": capabilities,
+                "initializationOptions": { "ghosttyVersion": "1.2.0" },
+            }),
+        );
+        assert_eq!(init["serverInfo"]["name"], "ghostty-lsp");
+        self.notify("initialized", json!({}));
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "ghostty",
+                    "version": 1,
+                    "text": "font-size = 12\n",
+                }
+            }),
+        );
+        let hover = self.request(
+            2,
+            "textDocument/hover",
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 0, "character": 2 },
+            }),
+        );
+        assert_eq!(self.request(3, "shutdown", Value::Null), Value::Null);
+        self.notify("exit", Value::Null);
+        hover["contents"]["value"].as_str().unwrap().to_string()
+    }
+}
+
+#[test]
+fn test_socket_serves_several_clients() {
+    let path = std::env::temp_dir().join(format!("ghostty-lsp-it-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_ghostty-lsp"))
+            .arg("--socket")
+            .arg(&path)
+            .env("RUST_LOG", "off")
+            .spawn()
+            .unwrap(),
+        path.clone(),
+    );
+
+    // Two editors at once, then one more after they are gone.
+    let mut first = Client::connect(&path);
+    let mut second = Client::connect(&path);
+    let second = std::thread::spawn(move || second.session("file:///second/config"));
+    assert_eq!(first.session("file:///first/config"), "Font size in points");
+    assert_eq!(second.join().unwrap(), "Font size in points");
+    let mut third = Client::connect(&path);
+    assert_eq!(third.session("file:///third/config"), "Font size in points");
+}